@@ -0,0 +1,167 @@
+use std::fs;
+use std::sync::LazyLock;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::BesmcError;
+use super::language::Language;
+use super::listing::Severity;
+
+// Built-in catalog of messages.
+const BUILTIN_CATALOG: &str = include_str!("catalog.toml");
+
+static BUILTIN: LazyLock<Catalog> = LazyLock::new(|| {
+    let mut catalog = Catalog::default();
+    catalog.extend(BUILTIN_CATALOG, "built-in catalog").expect("Invalid built-in catalog");
+    catalog
+});
+
+//
+// Message of a compiler or loader, which can be found in the listing.
+//
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    /// Unique name
+    pub id: String,
+
+    /// Compilers which print this message; any compiler when empty
+    pub languages: Vec<Language>,
+
+    /// Message of the loader, which does not belong to any source
+    pub loader: bool,
+
+    pub severity: Severity,
+    pub regex: Regex,
+
+    /// What the message means, in English
+    pub description: String,
+
+    /// Message is about the source as a whole, not about a line
+    pub whole_unit: bool,
+}
+
+//
+// Pattern as written in the TOML file.
+//
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PatternEntry {
+    id: String,
+    #[serde(default)]
+    languages: Vec<String>,
+    #[serde(default)]
+    loader: bool,
+    #[serde(default)]
+    severity: Option<Severity>,
+    #[serde(default)]
+    regex: Option<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    whole_unit: bool,
+    #[serde(default)]
+    enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogFile {
+    #[serde(default)]
+    pattern: Vec<PatternEntry>,
+}
+
+//
+// Catalog of messages, by compiler.
+//
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    patterns: Vec<Pattern>,
+}
+
+impl Catalog {
+    //
+    // Catalog of messages besmc knows.
+    //
+    pub fn builtin() -> &'static Catalog {
+        &BUILTIN
+    }
+
+    //
+    // Built-in catalog, extended by patterns from the given TOML file.
+    //
+    pub fn load(file: &str) -> Result<Catalog, BesmcError> {
+        let text = fs::read_to_string(file)
+                      .map_err(|e| BesmcError::io("read", file, e))?;
+        let mut catalog = Catalog::builtin().clone();
+        catalog.extend(&text, file)?;
+        Ok(catalog)
+    }
+
+    //
+    // Add patterns from TOML text.
+    // Pattern with a known id replaces the old one, or removes it when disabled.
+    // New patterns go before the old ones, so they take priority.
+    //
+    pub fn extend(&mut self, text: &str, file: &str) -> Result<(), BesmcError> {
+        let bad = |message: String| BesmcError::BadPatterns { file: file.to_string(), message };
+        let parsed: CatalogFile = toml::from_str(text).map_err(|e| bad(e.to_string()))?;
+
+        let mut added = Vec::new();
+        for entry in parsed.pattern {
+            let old = self.patterns.iter().position(|p| p.id == entry.id);
+            if entry.enabled == Some(false) {
+                if let Some(index) = old {
+                    self.patterns.remove(index);
+                }
+                continue;
+            }
+            let Some(regex) = &entry.regex else {
+                return Err(bad(format!("pattern {}: missing regex", entry.id)));
+            };
+            let regex = Regex::new(regex).map_err(|e| bad(format!("pattern {}: {}", entry.id, e)))?;
+            let mut languages = Vec::new();
+            for name in &entry.languages {
+                let language = Language::from_extension(name)
+                    .ok_or_else(|| bad(format!("pattern {}: unknown language {}", entry.id, name)))?;
+                languages.push(language);
+            }
+            let pattern = Pattern {
+                id: entry.id,
+                languages,
+                loader: entry.loader,
+                severity: entry.severity.unwrap_or(Severity::Error),
+                regex,
+                description: entry.description,
+                whole_unit: entry.whole_unit,
+            };
+            match old {
+                Some(index) => self.patterns[index] = pattern,
+                None => added.push(pattern),
+            }
+        }
+        added.append(&mut self.patterns);
+        self.patterns = added;
+        Ok(())
+    }
+
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    //
+    // Find the pattern, which matches a line of the listing.
+    // Patterns of the compiler of the current unit go first,
+    // then patterns for any compiler, then patterns of the loader.
+    // Outside of units, when no banner of a compiler is found,
+    // patterns of all compilers are tried.
+    //
+    pub fn classify(&self, line: &str, language: Option<Language>) -> Option<&Pattern> {
+        let own = |p: &&Pattern| !p.loader && language.is_none_or(|l| p.languages.contains(&l));
+        let any = |p: &&Pattern| !p.loader && p.languages.is_empty();
+        let loader = |p: &&Pattern| p.loader;
+        let matches = |p: &&Pattern| p.regex.is_match(line);
+        self.patterns.iter().filter(own).find(matches)
+            .or_else(|| self.patterns.iter().filter(any).find(matches))
+            .or_else(|| self.patterns.iter().filter(loader).find(matches))
+    }
+}
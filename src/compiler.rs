@@ -1,78 +1,68 @@
 use std::fs;
-use std::io::{self, Write, BufRead, BufReader};
-use std::process::Command;
+use std::io::{self, Write};
 use std::path::Path;
-use std::process::Stdio;
 use std::os::unix::fs::PermissionsExt;
-use regex::Regex;
-
-use super::{CompilerOptions};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{CompilerOptions, BuildArtifacts, BesmcError};
+use super::workdir::WorkDir;
+use super::catalog::Catalog;
+use super::entry;
+use super::plan::{Emit, Library, Plan, ScriptPart, ToolCommand};
+use super::stdarray::StdArray;
+use super::language::Language;
+use super::listing::{self, Diagnostic, Severity, Unit};
+use super::messages;
+use super::linkmap;
+use super::loader::{self, LinkSummary};
+use super::warnings;
+
+// Most object files one Dubna job can load: perso disks 40 to 57.
+const MAX_OBJECTS: usize = 16;
+
+// Disks for libraries given by -l: after output disk 60, up to 77.
+const FIRST_LIBRARY_UNIT: usize = 0o61;
+const LAST_LIBRARY_UNIT: usize = 0o77;
 
 //
 // Writes the contents of a source file to an already opened destination file.
 //
-fn copy_file_contents(mut dest_file: &fs::File, src_filename: &str)  {
+fn copy_file_contents(mut dest_file: &fs::File, src_filename: &str) -> Result<(), BesmcError> {
     let mut src_file = fs::File::open(src_filename)
-                                .unwrap_or_else(|e| { panic!("Failed to open file '{}': {}", src_filename, e); });
+                                .map_err(|e| BesmcError::io("open file", src_filename, e))?;
     io::copy(&mut src_file, &mut dest_file)
-       .unwrap_or_else(|e| { panic!("Failed to copy to destination: {}", e); });
-}
-
-//
-// Writes the contents of many source files with given prefix
-// to an already opened destination file.
-//
-fn copy_file(mut dest_file: &fs::File, src: &str, prefix: &str)  {
-    dest_file.write_all(prefix.as_bytes())
-             .unwrap_or_else(|e| { panic!("Failed to write prefix: {}", e); });
-    copy_file_contents(&dest_file, src);
+       .map_err(|e| BesmcError::io("copy", src_filename, e))?;
+    Ok(())
 }
 
 //
 // Add *file:persoNN directive to the script.
 // Here NN=40...57 (octal).
-// Copy .obj file to a temporary 'persNN.bin' file.
+// Object file is to be copied to a temporary 'persNN.bin' file in the work directory.
 // Increment perso index.
 // Return name of the temporary file.
 //
-fn create_perso_file(mut script_file: &fs::File, obj_filename: &str, perso_index: &mut i32) -> String
+fn create_perso_file(script: &mut Vec<ScriptPart>, obj_filename: &str, perso_index: &mut i32, workdir: &WorkDir) -> Result<String, BesmcError>
 {
     if *perso_index >= 0o60 {
-        panic!("Cannot process {}: too many object files", obj_filename);
+        return Err(BesmcError::TooManyObjects { file: obj_filename.to_string() });
     }
-    writeln!(script_file, "*file:pers{:o},{:o}", *perso_index, *perso_index)
-        .unwrap_or_else(|e| { panic!("Failed to write *file: {}", e); });
+    script.push(ScriptPart::Text(format!("*file:pers{:o},{:o}\n", *perso_index, *perso_index)));
 
-    let bin_filename = format!("pers{:o}.bin", *perso_index);
+    let bin_filename = workdir.file(&format!("pers{:o}.bin", *perso_index));
     *perso_index += 1;
-
-    // Copy file contents.
-    let _ = fs::copy(obj_filename, &bin_filename)
-               .unwrap_or_else(|e| { panic!("Failed to copy {} to {}: {}", obj_filename, bin_filename, e); });
-
-    // Return name of temporary file.
-    bin_filename
-}
-
-//
-// Remove file and check status.
-//
-fn remove_file(filename: &str) {
-    match fs::remove_file(filename) {
-        Ok(()) => {}, // File removed
-        Err(e) if e.kind() == io::ErrorKind::NotFound => {}, // File not found, nothing to remove
-        Err(e) => println!("Cannot remove '{}': {}", filename, e),
-    }
+    Ok(bin_filename)
 }
 
 //
-// Remove file and check status.
+// Set executable permission bits on a file.
 //
-fn make_file_executable(file_path: &str) {
+fn make_file_executable(file_path: &str) -> Result<(), BesmcError> {
 
     // Get the current permissions of the file
     let metadata = fs::metadata(file_path)
-                      .unwrap_or_else(|e| { panic!("Cannot get metadata for {}: {}", file_path, e); });
+                      .map_err(|e| BesmcError::io("get metadata for", file_path, e))?;
     let mut permissions = metadata.permissions();
 
     // Set executable bits.
@@ -80,7 +70,7 @@ fn make_file_executable(file_path: &str) {
 
     // Apply the new permissions to the file
     fs::set_permissions(file_path, permissions)
-        .unwrap_or_else(|e| { panic!("Cannot set permissions for {}: {}", file_path, e); });
+        .map_err(|e| BesmcError::io("set permissions for", file_path, e))
 }
 
 //
@@ -89,7 +79,7 @@ fn make_file_executable(file_path: &str) {
 // Comparison is case-insensitive, e.g., ".PDF" and ".pdf" are treated the same.
 // Returns true if the file has the extension.
 //
-fn has_extension(filename: &str, ext_with_dot: &str) -> bool {
+pub(crate) fn has_extension(filename: &str, ext_with_dot: &str) -> bool {
     let ext_lower = ext_with_dot.to_lowercase();
     filename.to_lowercase().ends_with(&ext_lower)
 }
@@ -110,146 +100,693 @@ fn besm6_share_dirs() -> Vec<String> {
 
 //
 // Locate the directory with BESM-6 C header files (*.h).
-// Panic with a helpful message if not found.
+// Fail with a helpful message if not found.
 //
-fn find_besm6_include_dir() -> String {
+fn find_besm6_include_dir() -> Result<String, BesmcError> {
     let mut probed = Vec::new();
     for base in besm6_share_dirs() {
         let dir = format!("{}/include", base);
         if Path::new(&dir).is_dir() {
-            return dir;
+            return Ok(dir);
         }
         probed.push(dir);
     }
-    panic!("BESM-6 C headers not found. Looked in:\n  {}", probed.join("\n  "));
+    Err(BesmcError::MissingHeaders { probed })
 }
 
 //
 // Locate the BESM-6 libc binary (libc.bin).
-// Panic with a helpful message if not found.
+// Fail with a helpful message if not found.
 //
-fn find_libc_path() -> String {
+fn find_libc_path() -> Result<String, BesmcError> {
     let mut probed = Vec::new();
     for base in besm6_share_dirs() {
         let lib = format!("{}/lib/libc.bin", base);
         if Path::new(&lib).is_file() {
-            return lib;
+            return Ok(lib);
         }
         probed.push(lib);
     }
-    panic!("BESM-6 libc.bin not found. Looked in:\n  {}", probed.join("\n  "));
+    Err(BesmcError::MissingLibc { probed })
 }
 
 //
-// Run a compiler pass and panic if it fails.
+// Locate object library libNAME.obj given by -l option.
+// Directories given by -L are searched first, then lib/ under BESM-6 share directories.
 //
-fn run_pass(program: &str, args: &[&str]) {
-    let status = Command::new(program)
-                         .args(args)
-                         .status()
-                         .unwrap_or_else(|e| { panic!("Failed to execute {}: {}", program, e); });
-    if !status.success() {
-        panic!("{} failed with status: {}", program, status);
+fn find_library(name: &str, library_dirs: &[String]) -> Result<String, BesmcError> {
+    let share_dirs = besm6_share_dirs().into_iter().map(|base| format!("{}/lib", base));
+    let mut probed = Vec::new();
+    for dir in library_dirs.iter().cloned().chain(share_dirs) {
+        let lib = format!("{}/lib{}.obj", dir, name);
+        if Path::new(&lib).is_file() {
+            return Ok(lib);
+        }
+        probed.push(lib);
     }
+    Err(BesmcError::LibraryNotFound { name: name.to_string(), probed })
 }
 
-// Function to search for patterns in a file
-fn search_errors_in_listing(file_path: &str, _options: &CompilerOptions) -> bool {
-
-    // List of possible error messages
-    let patterns = vec![
-        String::from(r"БЫЛИ OШИБKИ ПPИ BBOДE ИЛИ TPAHCЛЯЦИИ"),
-        String::from(r"HET ′EOP′"),
-        String::from(r"OTCYTCTBYET ИMЯ ПPOГPAMMЫ"),
-        String::from(r"OTCYTCTBYET ИMЯ ПOДПPOГPAMMЫ"),
-        String::from(r"OTCYTCTBYET ЗAГOЛOBOK ПOДПPOГPAMMЫ"),
-        String::from(r"OTCYTCTBYET  PROGRAM"),
-        String::from(r"ЗHAЧEH.* HE OПPEДEЛEHO"),
-        String::from(r"INCORRECT ALGOL PROGRAM"),
-        String::from(r"\*\*\*\*\*\*HEOПИCAHHЫЙ ИДEHTИФИKATOP"),
-        String::from(r"\*\*\*\*\*\* HEOПИCAHHЫЙ ИДEHTИФИKATOP:"),
-        String::from(r"^ \*\*\*\*\*\*\d+ "),
-        String::from(r"^HEOП MET "),
-        String::from(r"^ERROR \d+"),
-        String::from(r"^ ERROR \d+"),
-        String::from(r"OTCYTCTBYET"),
-        String::from(r"HEДOПYCTИMЫЙ OПEPATOP:"),
-        String::from(r"ДЛИHHЫЙ AДPEC B"),
-    ];
-
-    // Create a vector of compiled regex patterns
-    let regexes: Vec<Regex> = patterns
-        .into_iter()
-        .map(|p| Regex::new(&p).expect("Invalid regex pattern"))
-        .collect();
+//
+// Names of output binary and listing.
+// The first source file defines them, unless -o option is given.
+//
+fn output_names(options: &CompilerOptions) -> Result<(String, String), BesmcError> {
+    let first_file = options.files.first()
+                                  .ok_or(BesmcError::NoInput)?;
+    let output_option = options.output_file.clone()
+                               .unwrap_or(first_file.clone());
+    let output_path = Path::new(&output_option);
+    let output_extension = if options.stop_at_object { options.emit.extension() } else { "exe" };
+    let output_file = output_path.with_extension(output_extension).to_string_lossy().into_owned();
+    let listing_file = output_path.with_extension("lst").to_string_lossy().into_owned();
+    Ok((output_file, listing_file))
+}
 
-    // Open the file and create a buffered reader - panic on error
-    let file = fs::File::open(file_path).unwrap_or_else(|e|
-        panic!("Failed to open file {}: {}", file_path, e)
-    );
-    let reader = BufReader::new(file);
-
-    // Flag to track if any matches were found
-    let mut has_matches = false;
-
-    // Process each line - panic on read error
-    for (_line_num, line) in reader.lines().enumerate() {
-        let line = line.unwrap_or_else(|e|
-            panic!("Failed to read line from {}: {}", file_path, e)
-        );
-        for regex in &regexes {
-            if regex.is_match(&line) {
-                println!("{}", line);
-                has_matches = true;
-                break; // Move to next line after first match
-            }
+//
+// Plan of compiling files by one Dubna job, without running anything.
+// Temporary files are shown in "$WORKDIR" directory.
+// When there are too many object files, this is the plan of the last job,
+// which loads the merged libraries.
+//
+pub fn plan_files(options: &CompilerOptions) -> Result<Plan, BesmcError> {
+    let (output_file, listing_file) = output_names(options)?;
+    let workdir = WorkDir::placeholder();
+    let (_, files) = plan_merges(&options.files, &workdir);
+    let options = CompilerOptions { files, ..options.clone() };
+    plan_job(&options, &output_file, &listing_file, &workdir)
+}
+
+//
+// Plans of all Dubna jobs of the build, in order they are run, as --dry-run shows them:
+// jobs of separate sources with -j or --split-objects, merge jobs, and the last job.
+//
+pub fn plan_jobs(options: &CompilerOptions) -> Result<Vec<Plan>, BesmcError> {
+    let (output_file, listing_file) = output_names(options)?;
+    let workdir = WorkDir::placeholder();
+    let mut plans = Vec::new();
+    if options.stop_at_object && options.split_objects {
+        for job in split_jobs(options)?.0 {
+            plans.extend(plan_merged_job(&job.options, &job.output, &job.listing, &workdir)?);
+        }
+        return Ok(plans);
+    }
+    let parallel = options.jobs.is_some_and(|n| n != 1);
+    if parallel && options.stop_at_object && options.files.len() > 1 {
+        let (jobs, objects) = parallel_jobs(options, &workdir);
+        for job in &jobs {
+            plans.extend(plan_merged_job(&job.options, &job.output, &job.listing, &workdir)?);
         }
+        let merge_options = CompilerOptions { files: objects, jobs: None, ..options.clone() };
+        plans.extend(plan_merged_job(&merge_options, &output_file, &workdir.file("merge.lst"), &workdir)?);
+        return Ok(plans);
+    }
+    plan_merged_job(options, &output_file, &listing_file, &workdir)
+}
+
+//
+// Plans of one job of compile_job(): merges of its objects, then the job itself.
+//
+fn plan_merged_job(options: &CompilerOptions, output_file: &str, listing_file: &str, workdir: &WorkDir) -> Result<Vec<Plan>, BesmcError> {
+    let (merges, files) = plan_merges(&options.files, workdir);
+    let mut plans = Vec::new();
+    for merge in &merges {
+        plans.push(plan_job(&merge.options(options), &merge.output, &merge.listing, workdir)?);
     }
-    has_matches
+    plans.push(plan_job(&CompilerOptions { files, ..options.clone() }, output_file, listing_file, workdir)?);
+    Ok(plans)
 }
 
 //
 // Compiles files based on options.
 // If -c is set, compile .ftn and .assem and everything else to .obj, then stop.
 // Otherwise compile everything, then link into .exe.
+// With -c and -j, every source is compiled by a separate Dubna job,
+// and the resulting objects are merged into one library.
 //
-pub fn compile_files(options: &CompilerOptions) {
+pub fn compile_files(options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
 
-    // The first source file defines names of output binary and listing.
-    let first_file = &options.files[0];
-    let output_option = options.output_file.clone()
-                               .unwrap_or(first_file.clone());
-    let output_path = Path::new(&output_option);
-    let output_extension = if options.stop_at_object { "obj" } else { "exe" };
-    let output_file = output_path.with_extension(output_extension).to_string_lossy().into_owned();
-    let listing_file = output_path.with_extension("lst").to_string_lossy().into_owned();
-    let script_file = output_path.with_extension("dub").to_string_lossy().into_owned();
+    let (output_file, listing_file) = output_names(options)?;
+    if options.stop_at_object && options.split_objects {
+        return compile_split(options);
+    }
+    let parallel = options.jobs.is_some_and(|n| n != 1);
+    if parallel && options.stop_at_object && options.files.len() > 1 {
+        return compile_parallel(options, &output_file, &listing_file);
+    }
+    compile_job(options, &output_file, &listing_file)
+}
+
+//
+// Separate Dubna job: compile input files of the options
+// into output file, and write listing.
+//
+struct Job {
+    options: CompilerOptions,
+    output: String,
+    listing: String,
+}
 
-    // Create a list of files to remove when done.
-    let mut files_to_remove: Vec<String> = vec![
-        "output.bin".to_string(),
-        script_file.clone(),
-    ];
+impl Job {
+    fn new(options: &CompilerOptions, file: &str, output: String, listing: String) -> Self {
+        let options = CompilerOptions {
+            files: vec![file.to_string()],
+            output_file: Some(output.clone()),
+            jobs: None,
+            split_objects: false,
+            map: None,
+            ..options.clone()
+        };
+        Job { options, output, listing }
+    }
+}
+
+//
+// Run jobs, up to given number at once.
+// Each worker takes the next job from the list.
+// Return results in the order of jobs.
+//
+pub(crate) fn run_jobs<T: Sync, R: Send>(jobs: &[T], count: usize, run: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let next_job = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<R>>> = jobs.iter().map(|_| Mutex::new(None)).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..count.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next_job.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+                    let result = run(job);
+                    *results[index].lock().unwrap() = Some(result);
+                }
+            });
+        }
+    });
+    results.into_iter()
+           .map(|result| result.into_inner().unwrap().expect("job was not run"))
+           .collect()
+}
+
+//
+// Compile jobs, up to given number at once.
+//
+fn compile_jobs(jobs: &[Job], count: usize) -> Vec<Result<BuildArtifacts, BesmcError>> {
+    run_jobs(jobs, count, |job| compile_job(&job.options, &job.output, &job.listing))
+}
+
+//
+// Jobs of compile_split(): one per source, with objects named after sources.
+// Object files given as input are ignored, with a warning.
+//
+fn split_jobs(options: &CompilerOptions) -> Result<(Vec<Job>, Vec<String>), BesmcError> {
+    let mut warnings = Vec::new();
+    let sources: Vec<&String> = options.files.iter()
+        .filter(|file| {
+            let is_object = has_extension(file, ".obj");
+            if is_object {
+                warnings.push(format!("{}: object file ignored, nothing to compile", file));
+            }
+            !is_object
+        })
+        .collect();
+
+    // Decide where objects go.
+    let output_dir = match &options.output_file {
+        Some(output) if Path::new(output).is_dir() => Some(Path::new(output)),
+        Some(output) if sources.len() > 1 => {
+            return Err(BesmcError::OutputForManyFiles { output: output.clone() });
+        }
+        _ => None,
+    };
+    let mut jobs = Vec::new();
+    for file in &sources {
+        let object = match (&options.output_file, output_dir) {
+            (Some(output), None) => Path::new(output).with_extension(options.emit.extension()),
+            (_, dir) => {
+                let name = Path::new(file).with_extension(options.emit.extension());
+                dir.unwrap_or(Path::new("")).join(name.file_name().unwrap_or_default())
+            }
+        };
+        let listing = object.with_extension("lst").to_string_lossy().into_owned();
+        jobs.push(Job::new(options, file, object.to_string_lossy().into_owned(), listing));
+    }
+    Ok((jobs, warnings))
+}
+
+//
+// Compile each input file into its own object file, like "cc -c".
+// Objects are named after sources and created in the current directory.
+// Option -o gives the object name for a single input,
+// or the output directory for many inputs.
+//
+fn compile_split(options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    let (jobs, mut warnings) = split_jobs(options)?;
+
+    // Compile, and report the first failure in order of input files.
+    let mut artifacts = BuildArtifacts::default();
+    for (job, result) in jobs.iter().zip(compile_jobs(&jobs, number_of_jobs(options))) {
+        let job_artifacts = result?;
+        if artifacts.listing.is_empty() {
+            artifacts.listing = job.listing.clone();
+            artifacts.library_length = job_artifacts.library_length;
+        }
+        artifacts.objects.push(job.output.clone());
+        artifacts.diagnostics.extend(job_artifacts.diagnostics);
+        artifacts.link.merge(job_artifacts.link);
+        warnings.extend(job_artifacts.warnings);
+    }
+    artifacts.warnings = warnings;
+    Ok(artifacts)
+}
+
+//
+// Number of concurrent jobs requested by -j option.
+// Zero means one job per available CPU.
+// Dry run shows jobs one by one.
+//
+pub(crate) fn number_of_jobs(options: &CompilerOptions) -> usize {
+    if options.dry_run {
+        return 1;
+    }
+    match options.jobs {
+        Some(0) => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        Some(n) => n,
+        None    => 1,
+    }
+}
+
+//
+// Jobs of compile_parallel(): one per source, with objects and listings
+// in the work directory. Return the jobs, and all objects for the merge job.
+//
+fn parallel_jobs(options: &CompilerOptions, workdir: &WorkDir) -> (Vec<Job>, Vec<String>) {
+    // Object files need no compilation: they go directly to the merge step.
+    let mut jobs: Vec<Job> = Vec::new();
+    let mut objects = Vec::new();
+    for (index, file) in options.files.iter().enumerate() {
+        if has_extension(file, ".obj") {
+            objects.push(file.clone());
+            continue;
+        }
+        let stem = Path::new(file).file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let object = workdir.file(&format!("{:02}-{}.obj", index, stem));
+        let listing = workdir.file(&format!("{:02}-{}.lst", index, stem));
+        objects.push(object.clone());
+
+        // Separate jobs make object libraries, for the merge job to load.
+        let mut job = Job::new(options, file, object, listing);
+        job.options.emit = Emit::Obj;
+        jobs.push(job);
+    }
+    (jobs, objects)
+}
+
+//
+// Compile each input file into a separate object in parallel,
+// then merge them into a single library.
+// Listings of all jobs are concatenated into one listing file.
+//
+fn compile_parallel(options: &CompilerOptions, output_file: &str, listing_file: &str) -> Result<BuildArtifacts, BesmcError> {
+
+    // Objects and listings of separate jobs are kept in a work directory.
+    let workdir = if options.dry_run {
+        WorkDir::placeholder()
+    } else {
+        WorkDir::create(options.save_temps)?
+    };
+    if options.save_temps && !options.dry_run {
+        eprintln!("Temporary files are kept in {}", workdir.path().display());
+    }
+
+    let (jobs, objects) = parallel_jobs(options, &workdir);
+    let mut listings: Vec<String> = jobs.iter().map(|job| job.listing.clone()).collect();
+    let results = compile_jobs(&jobs, number_of_jobs(options));
+
+    // Keep messages of successful jobs, and the first failure.
+    let mut diagnostics = Vec::new();
+    let mut link = LinkSummary::default();
+    let mut failure = None;
+    for result in results {
+        match result {
+            Ok(artifacts) => {
+                diagnostics.extend(artifacts.diagnostics);
+                link.merge(artifacts.link);
+            }
+            Err(err) => { failure.get_or_insert(err); }
+        }
+    }
+
+    // Merge all objects into one library.
+    let merge_result = match failure {
+        Some(err) => Err(err),
+        None => {
+            let merge_listing = workdir.file("merge.lst");
+            listings.push(merge_listing.clone());
+            compile_job(&CompilerOptions { files: objects, jobs: None, ..options.clone() }, output_file, &merge_listing)
+        }
+    };
+
+    if options.dry_run {
+        return merge_result;
+    }
+
+    // Collect listings of all jobs, in order of input files.
+    let mut listing = fs::File::create(listing_file)
+                               .map_err(|e| BesmcError::io("create", listing_file, e))?;
+    for job_listing in &listings {
+        if Path::new(job_listing).exists() {
+            copy_file_contents(&listing, job_listing)?;
+        }
+    }
+    listing.flush()
+           .map_err(|e| BesmcError::io("flush", listing_file, e))?;
+
+    match merge_result {
+        Ok(artifacts) => {
+            diagnostics.extend(artifacts.diagnostics);
+            link.merge(artifacts.link);
+            Ok(BuildArtifacts {
+                listing: listing_file.to_string(),
+                diagnostics,
+                link,
+                ..artifacts
+            })
+        }
+        Err(BesmcError::CompilationFailed { diagnostics: failed, .. }) => {
+            diagnostics.extend(failed);
+            Err(BesmcError::CompilationFailed { listing: listing_file.to_string(), diagnostics })
+        }
+        Err(err) => Err(err),
+    }
+}
+
+//
+// Extra Dubna job, which merges object files into one library.
+//
+struct Merge {
+    objects: Vec<String>,
+    output: String,
+    listing: String,
+}
+
+impl Merge {
+    //
+    // Options of the merge job: make an object library of the objects.
+    //
+    fn options(&self, options: &CompilerOptions) -> CompilerOptions {
+        CompilerOptions {
+            files: self.objects.clone(),
+            output_file: Some(self.output.clone()),
+            stop_at_object: true,
+            emit: Emit::Obj,
+            map: None,
+            max_size: None,
+            ..options.clone()
+        }
+    }
+}
+
+//
+// Decide how to merge object files, so that no job loads more than MAX_OBJECTS.
+// Objects are merged in groups, by as many rounds as needed.
+// Return merge jobs in order, and the input files with merged libraries
+// in place of the objects.
+//
+fn plan_merges(files: &[String], workdir: &WorkDir) -> (Vec<Merge>, Vec<String>) {
+    let mut objects: Vec<String> = files.iter().filter(|f| has_extension(f, ".obj")).cloned().collect();
+    let mut merges = Vec::new();
+    let mut round = 0;
+    while objects.len() > MAX_OBJECTS {
+        let mut merged = Vec::new();
+        for (index, group) in objects.chunks(MAX_OBJECTS).enumerate() {
+            if group.len() == 1 {
+                merged.push(group[0].clone());
+                continue;
+            }
+            let name = format!("merged-{}-{:02}", round, index);
+            let output = workdir.file(&format!("{}.obj", name));
+            merges.push(Merge {
+                objects: group.to_vec(),
+                output: output.clone(),
+                listing: workdir.file(&format!("{}.lst", name)),
+            });
+            merged.push(output);
+        }
+        objects = merged;
+        round += 1;
+    }
+    if merges.is_empty() {
+        return (merges, files.to_vec());
+    }
+
+    // Merged libraries take the place of the first object file.
+    let mut result = Vec::new();
+    for file in files {
+        if !has_extension(file, ".obj") {
+            result.push(file.clone());
+        } else if !objects.is_empty() {
+            result.append(&mut objects);
+        }
+    }
+    (merges, result)
+}
+
+//
+// Run one merge job.
+// When it fails, its listing is copied to the listing of the build,
+// as the work directory is removed.
+//
+fn merge_objects(options: &CompilerOptions, merge: &Merge, listing_file: &str) -> Result<(), BesmcError> {
+    match compile_job(&merge.options(options), &merge.output, &merge.listing) {
+        Ok(_) => Ok(()),
+        Err(BesmcError::CompilationFailed { diagnostics, .. }) => {
+            fs::copy(&merge.listing, listing_file)
+               .map_err(|e| BesmcError::io("copy", &merge.listing, e))?;
+            Err(BesmcError::CompilationFailed { listing: listing_file.to_string(), diagnostics })
+        }
+        Err(err) => Err(err),
+    }
+}
+
+//
+// Whether the only error is the loader missing PROGRAM.
+//
+fn only_missing_program(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter()
+               .filter(|diagnostic| diagnostic.severity == Severity::Error)
+               .all(|diagnostic| diagnostic.rule == "loader-no-program")
+}
+
+//
+// Compile input files by one Dubna job.
+// Write the result to output_file, and the Dubna output to listing_file.
+// All temporary files are created in a private work directory,
+// which is removed when done, unless -t is given.
+// With --dry-run, only show what would be done.
+//
+pub(crate) fn compile_job(options: &CompilerOptions, output_file: &str, listing_file: &str) -> Result<BuildArtifacts, BesmcError> {
+
+    // Create work directory for temporary files.
+    let workdir = if options.dry_run {
+        WorkDir::placeholder()
+    } else {
+        WorkDir::create(options.save_temps)?
+    };
+    if options.save_temps && !options.dry_run {
+        eprintln!("Temporary files are kept in {}", workdir.path().display());
+    }
+
+    // When there are too many object files for one job,
+    // merge them into intermediate libraries first.
+    let (merges, files) = plan_merges(&options.files, &workdir);
+    for merge in &merges {
+        merge_objects(options, merge, listing_file)?;
+    }
+    let merged_options;
+    let options = if merges.is_empty() {
+        options
+    } else {
+        merged_options = CompilerOptions { files, ..options.clone() };
+        &merged_options
+    };
+
+    if options.dry_run {
+        let plan = plan_job(options, output_file, listing_file, &workdir)?;
+        println!("{}", plan);
+        return Ok(BuildArtifacts {
+            listing: listing_file.to_string(),
+            ..Default::default()
+        });
+    }
+    let plan = plan_job(options, output_file, listing_file, &workdir)?;
+
+    // Run compiler passes.
+    for command in &plan.commands {
+        command.run(options.verbose)?;
+    }
+
+    // Check standard arrays, given or made by pascompl, before Dubna loads them.
+    for part in &plan.script {
+        if let ScriptPart::Include(file) = part && has_extension(file, ".std") {
+            StdArray::load(file)?;
+        }
+    }
+
+    // Put object files and libraries into the work directory.
+    for (from, to) in &plan.copies {
+        fs::copy(from, to)
+           .map_err(|e| BesmcError::io("copy", from, e))?;
+    }
+    for (target, link) in &plan.symlinks {
+        std::os::unix::fs::symlink(target, link)
+            .map_err(|e| BesmcError::io("create symlink to", target, e))?;
+    }
+
+    // Create script for Dubna.
+    plan.write_script()?;
+
+    // Write listing to file.
+    let listing = fs::File::create(listing_file)
+                           .map_err(|e| BesmcError::io("create", listing_file, e))?;
+
+    // Run Dubna in the work directory.
+    // Show listing name as seen from there.
+    let listing_name = std::path::absolute(listing_file).unwrap_or(listing_file.into());
+    ToolCommand::new("dubna", &[&plan.script_name], &workdir.file(&plan.script_name))
+        .run_in(workdir.path(), listing, &listing_name.to_string_lossy(), options.verbose)?;
+
+    // Scan listing and find compilation errors.
+    let catalog = match &options.patterns {
+        Some(file) => Catalog::load(file)?,
+        None => Catalog::builtin().clone(),
+    };
+    let diagnostics = listing::scan_listing_file(&catalog, listing_file, &plan.units)?;
+    let mut diagnostics = warnings::apply_warning_options(diagnostics, options);
+    for diagnostic in &mut diagnostics {
+        diagnostic.message = messages::render(&diagnostic.message, options.messages);
+    }
+    let link = loader::parse_loader_output_file(listing_file)?;
+    let mut job_warnings = Vec::new();
+    if let Some(map_file) = &options.map {
+        if options.stop_at_object {
+            job_warnings.push(format!("{}: link map is not created with -c", map_file));
+        } else if !warnings::has_errors(&diagnostics) {
+            let text = fs::read(listing_file)
+                          .map_err(|e| BesmcError::io("read", listing_file, e))?;
+            let objects = linkmap::punch_objects(&plan, options)?;
+            linkmap::build_link_map(&String::from_utf8_lossy(&text), &plan, &objects)?.write(map_file)?;
+        }
+    }
+
+    // Standard array is punched to the listing: take it before the listing is rewritten.
+    let punched = if plan.punched && !warnings::has_errors(&diagnostics) {
+        let text = fs::read(listing_file)
+                      .map_err(|e| BesmcError::io("read", listing_file, e))?;
+        Some(StdArray::from_listing(&String::from_utf8_lossy(&text))
+                      .map_err(|(line, message)| BesmcError::BadStdArray { file: listing_file.to_string(), line, message })?)
+    } else {
+        None
+    };
+    if options.translate_listing {
+        messages::render_listing_file(listing_file, options.messages)?;
+    }
+    if warnings::has_errors(&diagnostics) {
+        if plan.entry_assumed && only_missing_program(&diagnostics) {
+            return Err(BesmcError::NoEntry { files: options.files.clone() });
+        }
+        return Err(BesmcError::CompilationFailed { listing: listing_file.to_string(), diagnostics });
+    }
+
+    // Check the memory budget.
+    if let (Some(limit), false) = (options.max_size, options.stop_at_object) {
+        let Some(words) = link.library_words() else {
+            return Err(BesmcError::UnknownSize { output: output_file.to_string(), listing: listing_file.to_string() });
+        };
+        if words > limit {
+            let largest = link.largest_modules(5);
+            return Err(BesmcError::TooLarge { output: output_file.to_string(), words, limit, largest });
+        }
+    }
+
+    if let Some(array) = punched {
+        array.write(output_file)?;
+        return Ok(BuildArtifacts {
+            library_length: link.library_length.clone(),
+            link,
+            diagnostics,
+            warnings: job_warnings,
+            listing: listing_file.to_string(),
+            object: Some(output_file.to_string()),
+            ..Default::default()
+        });
+    }
+
+    // Copy output.bin to output_file.
+    let output = fs::File::create(output_file)
+                          .map_err(|e| BesmcError::io("create", output_file, e))?;
+    if !options.stop_at_object {
+        // Add shebang line.
+        writeln!(&output, "#!/usr/bin/env dubna")
+            .map_err(|e| BesmcError::io("write shebang to", output_file, e))?;
+    }
+    copy_file_contents(&output, &workdir.file("output.bin"))?;
+
+    if !options.stop_at_object {
+        // Make output file executable.
+        drop(output);
+        make_file_executable(output_file)?;
+    }
+
+    let mut artifacts = BuildArtifacts {
+        library_length: link.library_length.clone(),
+        link,
+        diagnostics,
+        warnings: job_warnings,
+        listing: listing_file.to_string(),
+        ..Default::default()
+    };
+    if options.stop_at_object {
+        artifacts.object = Some(output_file.to_string());
+    } else {
+        artifacts.executable = Some(output_file.to_string());
+    }
+    Ok(artifacts)
+}
+
+//
+// Decide how to compile input files by one Dubna job:
+// which compiler passes to run, which files to put into the work directory,
+// and what Dubna script to create. Nothing is run here.
+//
+fn plan_job(options: &CompilerOptions, output_file: &str, listing_file: &str, workdir: &WorkDir) -> Result<Plan, BesmcError> {
+
+    if options.files.is_empty() {
+        return Err(BesmcError::NoInput);
+    }
+    let script_name = Path::new(output_file).with_extension("dub")
+                                            .file_name().unwrap_or_default()
+                                            .to_string_lossy().into_owned();
+    let mut plan = Plan {
+        workdir: workdir.path().to_string_lossy().into_owned(),
+        script_name,
+        listing: listing_file.to_string(),
+        output: output_file.to_string(),
+        executable: !options.stop_at_object,
+        punched: options.stop_at_object && options.emit == Emit::Std,
+        ..Default::default()
+    };
 
     // For each *.pas input, call pascompl and replace with *.std.
     let mut input_files = options.files.clone();
     for file in input_files.iter_mut() {
-        if has_extension(&file, ".pas") {
-            let path = Path::new(file);
-            let std_file = path.with_extension("std").to_string_lossy().into_owned();
+        if has_extension(file, ".pas") {
+            let std_name = Path::new(file).with_extension("std");
+            let std_file = workdir.file(&std_name.file_name().unwrap_or_default().to_string_lossy());
 
             // Run Pascal compiler.
-            let status = Command::new("pascompl")
-                                 .arg("-P")
-                                 .arg(&file)
-                                 .arg(&std_file)
-                                 .status()
-                                 .unwrap_or_else(|e| { panic!("Failed to execute pascompl: {}", e); });
-            if !status.success() {
-                panic!("Pascal compiler failed on {} with status: {}", file, status)
-            }
-            *file = std_file.clone();
-            files_to_remove.push(std_file);
+            plan.commands.push(ToolCommand::new("pascompl", &["-P", file, &std_file], file));
+            *file = std_file;
         }
     }
 
@@ -266,180 +803,180 @@ pub fn compile_files(options: &CompilerOptions) {
     // The resulting *.madlen is then assembled through the usual *madlen path.
     let mut include_dir = String::new();
     for file in input_files.iter_mut() {
-        if has_extension(&file, ".c") {
+        if has_extension(file, ".c") {
             if include_dir.is_empty() {
-                include_dir = find_besm6_include_dir();
+                include_dir = find_besm6_include_dir()?;
             }
             // Derive intermediate names by appending to the full source name
             // (e.g. hello.c -> hello.c.i ... hello.c.madlen). This keeps the final
             // ".madlen" extension so it routes through the normal *madlen path, while
             // never clobbering an unrelated hand-written "hello.madlen" next to it.
-            let i_file      = format!("{}.i", file);
-            let asn_file    = format!("{}.asn", file);
-            let tac_file    = format!("{}.tac", file);
-            let madlen_file = format!("{}.madlen", file);
+            let name        = Path::new(file).file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let i_file      = workdir.file(&format!("{}.i", name));
+            let asn_file    = workdir.file(&format!("{}.asn", name));
+            let tac_file    = workdir.file(&format!("{}.tac", name));
+            let madlen_file = workdir.file(&format!("{}.madlen", name));
 
             // Use the traditional positional "cpp [options] infile outfile" form with a
             // joined -I<dir>: when /usr/bin/cpp is clang (e.g. macOS), the -o and spaced
             // -I forms are misparsed, while this form works on both GNU cpp and clang.
             let inc_flag = format!("-I{}", include_dir);
-            run_pass("cpp", &["-E", "-nostdinc", &inc_flag, &file, &i_file]);
-            run_pass("b6parse", &[&i_file, &asn_file]);
-            run_pass("b6lower", &[&asn_file, &tac_file]);
-            run_pass("b6codegen", &[&tac_file, &madlen_file]);
-
-            *file = madlen_file.clone();
-            files_to_remove.push(i_file);
-            files_to_remove.push(asn_file);
-            files_to_remove.push(tac_file);
-            files_to_remove.push(madlen_file);
+            plan.commands.push(ToolCommand::new("cpp", &["-E", "-nostdinc", &inc_flag, file, &i_file], file));
+            plan.commands.push(ToolCommand::new("b6parse", &[&i_file, &asn_file], file));
+            plan.commands.push(ToolCommand::new("b6lower", &[&asn_file, &tac_file], file));
+            plan.commands.push(ToolCommand::new("b6codegen", &[&tac_file, &madlen_file], file));
+
+            *file = madlen_file;
         }
     }
 
     // When linking C code, mount libc as a virtual disk file. The library lives
-    // outside the working directory, so create a temporary symlink to it here
+    // outside the work directory, so create a symlink to it there
     // (dubna reads 'libc.bin' from the current directory for *file:libc).
     let link_libc = has_c_files && !options.stop_at_object;
     if link_libc {
-        let libc_path = find_libc_path();
-        remove_file("libc.bin");
-        std::os::unix::fs::symlink(&libc_path, "libc.bin")
-            .unwrap_or_else(|e| { panic!("Failed to create libc.bin symlink to {}: {}", libc_path, e); });
-        files_to_remove.push("libc.bin".to_string());
+        let libc_path = find_libc_path()?;
+        plan.symlinks.push((libc_path, workdir.file("libc.bin")));
+    }
+
+    // Libraries given by -l are mounted as disks 61, 62 and so on,
+    // and searched by the loader, so only referenced modules are linked.
+    let mut libraries = Vec::new();
+    if !options.stop_at_object {
+        let limit = LAST_LIBRARY_UNIT - FIRST_LIBRARY_UNIT + 1;
+        if options.libraries.len() > limit {
+            return Err(BesmcError::TooManyLibraries { count: options.libraries.len(), limit });
+        }
+        for (index, name) in options.libraries.iter().enumerate() {
+            let unit = FIRST_LIBRARY_UNIT + index;
+            let path = find_library(name, &options.library_dirs)?;
+            let target = std::path::absolute(&path).map_or(path.clone(), |p| p.to_string_lossy().into_owned());
+            plan.symlinks.push((target, workdir.file(&format!("lib{:o}.bin", unit))));
+            libraries.push((unit, path));
+        }
     }
 
     // Create script for Dubna.
-    let mut script = fs::File::create(&script_file)
-                              .unwrap_or_else(|e| { panic!("Failed to create build.dub: {}", e); });
-    writeln!(script, "*name compile\n\
-                      *disc:1/local\n\
-                      *file:output,60,w")
-        .unwrap_or_else(|e| { panic!("Failed to write build.dub: {}", e); });
+    let script = &mut plan.script;
+    script.push(ScriptPart::Text("*name compile\n\
+                                  *disc:1/local\n\
+                                  *file:output,60,w\n".to_string()));
 
     // Mount the C runtime library when linking C code.
     if link_libc {
-        writeln!(script, "*file:libc,37")
-            .unwrap_or_else(|e| { panic!("Failed to write *file:libc: {}", e); });
+        script.push(ScriptPart::Text("*file:libc,37\n".to_string()));
+    }
+
+    // Mount libraries given by -l.
+    for (unit, _) in &libraries {
+        script.push(ScriptPart::Text(format!("*file:lib{:o},{:o}\n", unit, unit)));
     }
 
     // Add *file:persNN directive for each .obj file.
     let mut perso_index = 0o40;
     for file in &input_files {
-        let path = Path::new(file);
-        if let Some(ext) = path.extension() {
-            if ext.to_string_lossy().as_ref() == "obj" {
-                files_to_remove.push(create_perso_file(&script, file, &mut perso_index));
-            }
+        if has_extension(file, ".obj") {
+            let bin_file = create_perso_file(script, file, &mut perso_index, workdir)?;
+            plan.copies.push((file.clone(), bin_file));
         }
     }
 
     // Add B language tape and library if any .b file is present.
     let has_b_files = input_files.iter().any(|f| has_extension(f, ".b"));
     if has_b_files {
-        writeln!(script, "*tape:7/b,40\n\
-                          *library:40")
-            .unwrap_or_else(|e| { panic!("Failed to write build.dub: {}", e); });
+        script.push(ScriptPart::Text("*tape:7/b,40\n\
+                                      *library:40\n".to_string()));
+    }
+
+    // Punch compiled modules as a standard array, instead of saving them.
+    if plan.punched {
+        script.push(ScriptPart::Text("*punch\n".to_string()));
     }
 
     // Set single-page listing mode.
-    writeln!(script, "*call setftn:one,long")
-        .unwrap_or_else(|e| { panic!("Failed to write build.dub: {}", e); });
+    script.push(ScriptPart::Text("*call setftn:one,long\n".to_string()));
 
     // Write contents of each source file
     perso_index = 0o40;
-    for file in &input_files {
+    for (source, file) in options.files.iter().zip(&input_files) {
         let path = Path::new(file);
         if let Some(ext) = path.extension() {
-            match ext.to_string_lossy().as_ref() {
-                "ftn"     => copy_file(&script, &file, "*ftn\n"),
-                "fortran" => copy_file(&script, &file, "*fortran\n"),
-                "forex"   => copy_file(&script, &file, "*forex\n"),
-                "algol"   => copy_file(&script, &file, "*algol\n"),
-                "pascal"  => copy_file(&script, &file, "*pascal\n"),
-                "assem"   => copy_file(&script, &file, "*assem\n"),
-                "madlen"  => copy_file(&script, &file, "*madlen\n"),
-                "bemsh"   => copy_file(&script, &file, "*bemsh\n"),
-                "b"       => copy_file(&script, &file, "*trans-main:40020\n"),
+            let prefix = match ext.to_string_lossy().as_ref() {
+                "ftn"     => "*ftn\n",
+                "fortran" => "*fortran\n",
+                "forex"   => "*forex\n",
+                "algol"   => "*algol\n",
+                "pascal"  => "*pascal\n",
+                "assem"   => "*assem\n",
+                "madlen"  => "*madlen\n",
+                "bemsh"   => "*bemsh\n",
+                "b"       => "*trans-main:40020\n",
                 "obj"     => {
-                                writeln!(&script, "*call perso:{:o},cont", perso_index)
-                                    .unwrap_or_else(|e| { panic!("Failed to write *perso: {}", e); });
+                                script.push(ScriptPart::Text(format!("*call perso:{:o},cont\n", perso_index)));
+                                plan.libraries.push(Library {
+                                    directive: format!("*perso:{:o}", perso_index),
+                                    origin: source.clone(),
+                                });
                                 perso_index += 1;
+                                continue;
                             },
-                "std"     => copy_file(&script, &file, ""),
-                "exe"     => panic!("Cannot process executable file: {}", file),
-                _         => panic!("Unknown file extension: {}", file),
+                "std"     => "",
+                "exe"     => return Err(BesmcError::ExecutableInput { file: file.clone() }),
+                _         => return Err(BesmcError::UnknownExtension { file: file.clone() }),
+            };
+            if !prefix.is_empty() {
+                script.push(ScriptPart::Text(prefix.to_string()));
+            }
+            script.push(ScriptPart::Include(file.clone()));
+
+            // Remember the unit, to find its messages in the listing.
+            if let Some(language) = Language::from_file(source) {
+                plan.units.push(Unit { file: source.clone(), language });
             }
         } else {
-            panic!("Cannot process file without extension: {}", file);
+            return Err(BesmcError::UnknownExtension { file: file.clone() });
         }
     }
 
     // Write the final step.
-    if options.stop_at_object {
+    if plan.punched {
+        script.push(ScriptPart::Text("*end file\n".to_string()));
+    } else if options.stop_at_object {
         // Save as library of object files.
-        writeln!(script, "*call to perso: 60\n\
-                          *end file")
-            .unwrap_or_else(|e| { panic!("Failed to write {}: {}", script_file, e); });
+        script.push(ScriptPart::Text("*call to perso: 60\n\
+                                      *end file\n".to_string()));
     } else {
         // Create executable binary (overlay).
-        let entry = if has_extension(&first_file, ".bemsh") { "main" } else { "program" };
-        // Search the C runtime library before the system library.
-        if link_libc {
-            writeln!(script, "*library:37")
-                .unwrap_or_else(|e| { panic!("Failed to write *library:37: {}", e); });
+        let entry = match &options.entry {
+            Some(entry) => entry.clone(),
+            // Assume program, so that the compilers get to report problems
+            // of the sources before the missing entry is reported.
+            None => match entry::find_entry(&options.files) {
+                Err(BesmcError::NoEntry { .. }) => {
+                    plan.entry_assumed = true;
+                    "program".to_string()
+                }
+                result => result?,
+            },
+        };
+        // Search user libraries first, then the C runtime library, then the system library.
+        for (unit, path) in &libraries {
+            script.push(ScriptPart::Text(format!("*library:{:o}\n", unit)));
+            plan.libraries.push(Library { directive: format!("*library:{:o}", unit), origin: path.clone() });
         }
-        writeln!(script, "*library:22\n\
-                          *call overlay\n\
-                          {}\n\
-                          *end record\n\
-                          *end file", entry)
-            .unwrap_or_else(|e| { panic!("Failed to write {}: {}", script_file, e); });
-    }
-
-    // Ensure the file is written to disk
-    script.flush()
-          .unwrap_or_else(|e| { panic!("Failed to flush {}: {}", script_file, e); });
-    drop(script);
-
-    // Write listing to file.
-    let listing = fs::File::create(&listing_file)
-                           .unwrap_or_else(|e| { panic!("Failed to create {}: {}", listing_file, e); });
-
-    // Run Dubna.
-    let status = Command::new("dubna")
-                         .arg(&script_file)
-                         .stdout(Stdio::from(listing))
-                         .status()
-                         .unwrap_or_else(|e| { panic!("Failed to execute dubna: {}", e); });
-    if !status.success() {
-        panic!("Dubna failed with status: {}", status)
-    }
-
-    // Scan listing and find compilation errors.
-    if search_errors_in_listing(&listing_file, &options) {
-        panic!("---\nCompilation failed!\nSee details in {}", listing_file);
-    }
-
-    // Copy output.bin to output_file.
-    let output = fs::File::create(&output_file)
-                          .unwrap_or_else(|e| { panic!("Failed to create {}: {}", output_file, e); });
-    if !options.stop_at_object {
-        // Add shebang line.
-        writeln!(&output, "#!/usr/bin/env dubna")
-            .unwrap_or_else(|e| { panic!("Failed to write shebang: {}", e); });
-    }
-    copy_file_contents(&output, "output.bin");
-
-    // Remove temporary files.
-    if !options.save_temps {
-        for file_name in files_to_remove {
-            remove_file(&file_name);
+        if has_b_files {
+            plan.libraries.push(Library { directive: "*library:40".to_string(), origin: "B library (tape 7/b)".to_string() });
         }
+        if link_libc {
+            script.push(ScriptPart::Text("*library:37\n".to_string()));
+            plan.libraries.push(Library { directive: "*library:37".to_string(), origin: "C library (libc.bin)".to_string() });
+        }
+        plan.libraries.push(Library { directive: "*library:22".to_string(), origin: "system library".to_string() });
+        script.push(ScriptPart::Text(format!("*library:22\n\
+                                              *call overlay\n\
+                                              {}\n\
+                                              *end record\n\
+                                              *end file\n", entry)));
     }
-
-    if !options.stop_at_object {
-        // Make output file executable.
-        drop(output);
-        make_file_executable(&output_file);
-    }
+    Ok(plan)
 }
@@ -0,0 +1,117 @@
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use super::listing::Diagnostic;
+
+//
+// How to show diagnostics found in the listing.
+//
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DiagnosticsFormat {
+    /// One "file:line:column: error: message" line per diagnostic
+    #[default]
+    Text,
+
+    /// JSON array of records
+    Json,
+
+    /// SARIF 2.1.0 log
+    Sarif,
+}
+
+//
+// Diagnostic as a JSON record.
+//
+fn json_record(diagnostic: &Diagnostic) -> Value {
+    json!({
+        "severity": diagnostic.severity.to_string(),
+        "language": diagnostic.language.map(|language| language.name()),
+        "file": diagnostic.file,
+        "line": diagnostic.line,
+        "column": diagnostic.column,
+        "listing_line": diagnostic.listing_line,
+        "text": diagnostic.text,
+        "message": diagnostic.message,
+        "rule": diagnostic.rule,
+        "description": diagnostic.description,
+    })
+}
+
+//
+// Diagnostic as a SARIF result.
+//
+fn sarif_result(diagnostic: &Diagnostic) -> Value {
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": diagnostic.file },
+        }
+    });
+    if let Some(line) = diagnostic.line {
+        let mut region = json!({ "startLine": line });
+        if let Some(column) = diagnostic.column {
+            region["startColumn"] = json!(column);
+        }
+        location["physicalLocation"]["region"] = region;
+    }
+    json!({
+        "ruleId": diagnostic.rule,
+        "level": diagnostic.severity.to_string(),
+        "message": { "text": diagnostic.message },
+        "locations": [ location ],
+        "properties": {
+            "language": diagnostic.language.map(|language| language.name()),
+            "listingLine": diagnostic.listing_line,
+            "listingText": diagnostic.text,
+        }
+    })
+}
+
+//
+// Descriptions of patterns, which matched the diagnostics, without repeats.
+//
+fn sarif_rules(diagnostics: &[Diagnostic]) -> Vec<Value> {
+    let mut seen = Vec::new();
+    let mut rules = Vec::new();
+    for diagnostic in diagnostics {
+        if !seen.contains(&diagnostic.rule) {
+            seen.push(diagnostic.rule.clone());
+            rules.push(json!({
+                "id": diagnostic.rule,
+                "shortDescription": { "text": diagnostic.description },
+            }));
+        }
+    }
+    rules
+}
+
+//
+// Show diagnostics in given format.
+//
+pub fn format_diagnostics(format: DiagnosticsFormat, diagnostics: &[Diagnostic]) -> String {
+    let document = match format {
+        DiagnosticsFormat::Text => {
+            return diagnostics.iter()
+                              .map(|diagnostic| format!("{}\n", diagnostic))
+                              .collect();
+        }
+        DiagnosticsFormat::Json => {
+            Value::Array(diagnostics.iter().map(json_record).collect())
+        }
+        DiagnosticsFormat::Sarif => json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "besmc",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": "https://github.com/besm6/besmc",
+                        "rules": sarif_rules(diagnostics),
+                    }
+                },
+                "results": diagnostics.iter().map(sarif_result).collect::<Vec<_>>(),
+            }]
+        }),
+    };
+    format!("{:#}\n", document)
+}
@@ -0,0 +1,367 @@
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::Path;
+
+use super::{CompilerOptions, BuildArtifacts, BesmcError};
+use super::compiler::has_extension;
+use super::objlib::run_convert;
+use super::stdarray::{StdArray, StdModule};
+use super::workdir::WorkDir;
+
+// Instructions of short format, by 6-bit opcode 000-047; the rest are extracodes *50-*77.
+const SHORT_OPCODES: [&str; 0o50] = [
+    "atx", "stx", "mod", "xts", "a+x", "a-x", "x-a", "amx",
+    "xta", "aax", "aex", "arx", "avx", "aox", "a/x", "a*x",
+    "apx", "aux", "acx", "anx", "e+x", "e-x", "asx", "xtr",
+    "rte", "yta", "*32", "ext", "e+n", "e-n", "asn", "ntr",
+    "ati", "sti", "ita", "its", "mtj", "j+m", "*46", "*47",
+];
+
+// Instructions of long format, by opcode 200-370 divided by 010.
+const LONG_OPCODES: [&str; 16] = [
+    "*20", "*21", "utc", "wtc", "vtm", "utm", "uza", "u1a",
+    "uj",  "vjm", "ij",  "stop", "vzm", "v1m", "*36", "vlm",
+];
+
+// Bit of long format in a 24-bit instruction, and bit of extended address in short format.
+const LONG_FORMAT: u32 = 1 << 19;
+const EXTENDED_ADDRESS: u32 = 1 << 18;
+
+// Relocatable addresses in code of a module: 40000+N is word N of the code,
+// and 74000+N is symbol N of the table, counting from 1.
+const CODE_ADDRESS: u32 = 0o40000;
+const SYMBOL_ADDRESS: u32 = 0o74000;
+
+// Length of the passport, which follows the entries in the header of a module.
+const PASSPORT_WORDS: usize = 10;
+
+// Kinds of symbols, from bits 23-18 of the symbol word.
+const SYMBOL_LOCAL: u64 = 0o41;
+const SYMBOL_NAMED: u64 = 0o02;
+const SYMBOL_COMMON: u64 = 0o04;
+const SYMBOL_LONG_NAME: u64 = 0o20;
+
+//
+// Mnemonic of an instruction: "xta", or "*74" for extracodes.
+//
+pub fn mnemonic(instruction: u32) -> String {
+    if instruction & LONG_FORMAT != 0 {
+        let opcode = (instruction >> 12) & 0o370;
+        LONG_OPCODES[((opcode - 0o200) >> 3) as usize].to_string()
+    } else {
+        let opcode = (instruction >> 12) & 0o77;
+        match SHORT_OPCODES.get(opcode as usize) {
+            Some(name) => name.to_string(),
+            None => format!("*{:o}", opcode),
+        }
+    }
+}
+
+//
+// One 24-bit instruction in Madlen syntax: "14,vtm,77777", ",uj,", ",*74,".
+// Register and address are octal, and are omitted when zero.
+//
+pub fn disassemble_instruction(instruction: u32) -> String {
+    disassemble_relocatable(instruction, &|_| None)
+}
+
+//
+// Instruction with address shown by name, when the module gives it one.
+//
+fn disassemble_relocatable(instruction: u32, symbol: &dyn Fn(u32) -> Option<String>) -> String {
+    let register = (instruction >> 20) & 0o17;
+    let address = if instruction & LONG_FORMAT != 0 {
+        instruction & 0o77777
+    } else if instruction & EXTENDED_ADDRESS != 0 {
+        instruction & 0o7777 | 0o70000
+    } else {
+        instruction & 0o7777
+    };
+    let register = if register == 0 { String::new() } else { format!("{:o}", register) };
+    let address = match symbol(address) {
+        Some(name) => name,
+        None if address == 0 => String::new(),
+        None => format!("{:o}", address),
+    };
+    format!("{},{},{}", register, mnemonic(instruction), address)
+}
+
+//
+// Both instructions of a 48-bit word, left first.
+//
+pub fn disassemble_word(word: u64) -> (String, String) {
+    let left = (word >> 24) as u32 & 0o77777777;
+    let right = word as u32 & 0o77777777;
+    (disassemble_instruction(left), disassemble_instruction(right))
+}
+
+//
+// Whether a decoded string looks like a text constant, not like code.
+//
+fn looks_like_text(text: &str) -> bool {
+    let is_text_char = |c: char| c.is_ascii_alphanumeric() || " *.,'()+-/=:;!?\"$<>".contains(c);
+    text.chars().all(is_text_char) && text.chars().filter(|c| c.is_ascii_alphanumeric()).count() >= 3
+}
+
+//
+// Text of a word in ITM code, 8 characters of 6 bits: ASCII minus 040,
+// as used for names, like "MAIN" and "*OUTPUT*".
+//
+pub fn text_itm(word: u64) -> Option<String> {
+    let text = name_itm(word);
+    looks_like_text(&text).then(|| text.trim_end().to_string())
+}
+
+//
+// Name in ITM code, without trailing spaces, whatever it looks like.
+//
+fn name_itm(word: u64) -> String {
+    let text: String = (0..8).map(|i| char::from(((word >> (42 - 6 * i)) & 0o77) as u8 + 0o40)).collect();
+    text.trim_end().to_string()
+}
+
+//
+// Text of a word in 8-bit code, 6 characters, as used by compilers
+// for string constants, like "HELLO,". Zero bytes are shown as spaces.
+//
+pub fn text_bytes(word: u64) -> Option<String> {
+    let text: String = (0..6).map(|i| match ((word >> (40 - 8 * i)) & 0xff) as u8 {
+        0 => ' ',
+        byte => char::from(byte),
+    }).collect();
+    looks_like_text(&text).then(|| text.trim_end().to_string())
+}
+
+//
+// Layout of a module, as punched by the compilers: pairs of words for entries,
+// the passport, code, constants, and the table of symbols, which relocatable
+// addresses of the code refer to. Ranges are indices of words in the module.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleLayout {
+    /// Names of entries, with their addresses in the module
+    pub entries: Vec<(String, usize)>,
+
+    pub passport: Range<usize>,
+    pub code: Range<usize>,
+    pub constants: Range<usize>,
+
+    pub symbols: Vec<ModuleSymbol>,
+
+    /// Table of symbols and their long names
+    pub table: Range<usize>,
+}
+
+impl ModuleLayout {
+    //
+    // Words the module takes when loaded: code and constants.
+    //
+    pub fn program_words(&self) -> usize {
+        self.code.len() + self.constants.len()
+    }
+}
+
+//
+// Symbol of a module: what a relocatable address refers to.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleSymbol {
+    /// Name shown in place of the address: L00043, or name of external or common block
+    pub name: String,
+
+    /// What it is, like "external P/WL" or "common *OUTPUT*, 36 words"
+    pub description: String,
+}
+
+//
+// Find the layout of a module from its header.
+// The passport gives: word 1 - number of symbols, word 4 - number of entries,
+// word 5 - length of code, word 7 - length of constants.
+// Symbols are followed by long names, which they refer to by 4000+N,
+// counting from the word before the first symbol.
+// Return None when the words do not fit this layout.
+//
+pub fn module_layout(words: &[u64]) -> Option<ModuleLayout> {
+    let mut entry_count = 0;
+    while 2 * entry_count + 1 < words.len() && words[2 * entry_count] != 0 && words[2 * entry_count + 1] >> 45 == 2 {
+        entry_count += 1;
+    }
+    let passport = 2 * entry_count..2 * entry_count + PASSPORT_WORDS;
+    let field = |index: usize| words.get(passport.start + index).map(|&word| word as usize);
+    if entry_count == 0 || field(4)? != entry_count {
+        return None;
+    }
+    let code = passport.end..passport.end + field(5)?;
+    let constants = code.end..code.end + field(7)?;
+    let base = constants.end;
+    let symbol_count = field(1)?;
+    if base + symbol_count >= words.len() {
+        return None;
+    }
+
+    // Long name: 4000+N is word N from the base.
+    let mut table_end = base + symbol_count + 1;
+    let mut long_name = |reference: u64| {
+        let index = base + (reference as usize).checked_sub(0o4000)?;
+        table_end = table_end.max(index + 1);
+        words.get(index).map(|&word| name_itm(word))
+    };
+    let mut symbols: Vec<ModuleSymbol> = Vec::new();
+    for &word in &words[base + 1..=base + symbol_count] {
+        let reference = word >> 24;
+        let kind = (word >> 18) & 0o77;
+        let value = (word & 0o777777) as usize;
+        let local = |address: usize| ModuleSymbol { name: format!("L{:05o}", address), description: format!("address L{:05o}", address) };
+        let symbol = if kind == SYMBOL_LOCAL {
+            local(code.start + value)
+        } else if kind & SYMBOL_NAMED != 0 {
+            let name = if kind & SYMBOL_LONG_NAME != 0 {
+                long_name(reference)?
+            } else {
+                name_itm(reference << 24)
+            };
+            let description = if kind & SYMBOL_COMMON != 0 {
+                format!("common {}, {:o} words", name, value)
+            } else {
+                format!("external {}", name)
+            };
+            ModuleSymbol { name, description }
+        } else if kind == 0 {
+            // Offset from another symbol, like a string constant.
+            let target = symbols.get((reference as usize).checked_sub(0o4001)?)?;
+            match target.name.strip_prefix('L').and_then(|address| usize::from_str_radix(address, 8).ok()) {
+                Some(address) => local(address + value),
+                None => {
+                    let name = format!("{}+{:o}", target.name, value);
+                    ModuleSymbol { description: format!("address {}", name), name }
+                }
+            }
+        } else {
+            return None;
+        };
+        symbols.push(symbol);
+    }
+    if table_end > words.len() {
+        return None;
+    }
+    let entries = (0..entry_count).map(|i| {
+        let name = name_itm(words[2 * i]);
+        (name, code.start + (words[2 * i + 1] & 0o77777) as usize)
+    }).collect();
+    Some(ModuleLayout { entries, passport, code, constants, symbols, table: base..table_end })
+}
+
+//
+// Name of a relocatable address: word of the code, or a symbol.
+//
+fn symbol_name(layout: &ModuleLayout, address: u32) -> Option<String> {
+    if address > SYMBOL_ADDRESS {
+        return layout.symbols.get((address - SYMBOL_ADDRESS - 1) as usize).map(|symbol| symbol.name.clone());
+    }
+    if address >= CODE_ADDRESS && ((address - CODE_ADDRESS) as usize) < layout.code.len() {
+        return Some(format!("L{:05o}", layout.code.start + (address - CODE_ADDRESS) as usize));
+    }
+    None
+}
+
+//
+// Text of a word, when it looks like text.
+//
+fn word_text(word: u64) -> Option<String> {
+    text_bytes(word).map(|text| format!("\"{}\"", text))
+                    .or_else(|| text_itm(word).map(|text| format!("'{}'", text)))
+}
+
+//
+// Listing of one module.
+// Addresses are octal, counted from the start of the module, and shown
+// as L00016 in operands. Code is shown as instructions, with relocatable
+// addresses by name; other parts of the module are described.
+// When the layout is not known, every word is shown as instructions and text.
+//
+pub fn disassemble_module(module: &StdModule) -> String {
+    let mut text = String::new();
+    let words: Vec<u64> = module.words().collect();
+    let _ = writeln!(text, "Module {}: {} records, {} words", module.name, module.records.len(), words.len());
+    let layout = module_layout(&words);
+    if let Some(layout) = &layout {
+        let entries: Vec<String> = layout.entries.iter().map(|(name, address)| format!("{} at L{:05o}", name, address)).collect();
+        let _ = writeln!(text, "Entries: {}", entries.join(", "));
+    }
+    for (address, word) in words.iter().enumerate() {
+        let mut line = format!("{:05o}  {:016o}  ", address, word);
+        match &layout {
+            Some(layout) if layout.code.contains(&address) => {
+                let symbol = |address| symbol_name(layout, address);
+                let left = disassemble_relocatable((*word >> 24) as u32 & 0o77777777, &symbol);
+                let right = disassemble_relocatable(*word as u32 & 0o77777777, &symbol);
+                let _ = write!(line, "{:<16}  {}", left, right);
+            }
+            Some(layout) => {
+                let description = if address < layout.passport.start {
+                    layout.entries.get(address / 2).filter(|_| address % 2 == 0).map(|(name, _)| format!("entry {}", name))
+                } else if layout.passport.contains(&address) {
+                    Some("passport".to_string())
+                } else if address == layout.table.start {
+                    Some(format!("module {}", name_itm(*word)))
+                } else if address <= layout.table.start + layout.symbols.len() && address > layout.table.start {
+                    let number = address - layout.table.start;
+                    Some(format!("symbol {}: {}", number, layout.symbols[number - 1].description))
+                } else if layout.table.contains(&address) {
+                    Some(format!("long name {}", name_itm(*word)))
+                } else {
+                    word_text(*word)
+                };
+                if let Some(description) = description {
+                    line.push_str(&description);
+                }
+            }
+            None => {
+                let (left, right) = disassemble_word(*word);
+                let _ = write!(line, "{:<16}  {:<16}", left, right);
+                if let Some(constant) = word_text(*word) {
+                    let _ = write!(line, "  {}", constant);
+                }
+            }
+        }
+        let _ = writeln!(text, "{}", line.trim_end());
+    }
+    text
+}
+
+//
+// Listing of all modules of a standard array.
+//
+pub fn disassemble(array: &StdArray) -> String {
+    array.modules.iter().map(disassemble_module).collect::<Vec<_>>().join("\n")
+}
+
+//
+// Print the listing of a standard array file.
+// Object library is converted to a standard array first, by a Dubna job.
+//
+pub fn run_disasm(file: &str, options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    if has_extension(file, ".obj") {
+        let workdir = if options.dry_run {
+            WorkDir::placeholder()
+        } else {
+            WorkDir::create(options.save_temps)?
+        };
+        let name = Path::new(file).with_extension("std");
+        let std_file = workdir.file(&name.file_name().unwrap_or_default().to_string_lossy());
+        let artifacts = run_convert(file, &std_file, options)?;
+        if !options.dry_run {
+            print!("{}", disassemble(&StdArray::load(&std_file)?));
+        }
+        return Ok(artifacts);
+    }
+    if !has_extension(file, ".std") {
+        return Err(BesmcError::Unsupported {
+            what: format!("disassemble {}", file),
+            reason: "only standard arrays (.std) and object libraries (.obj) can be disassembled".to_string(),
+        });
+    }
+    let array = StdArray::load(file)?;
+    print!("{}", disassemble(&array));
+    Ok(BuildArtifacts::default())
+}
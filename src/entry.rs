@@ -0,0 +1,140 @@
+use std::fs;
+use std::sync::LazyLock;
+use regex::Regex;
+
+use super::BesmcError;
+use super::language::Language;
+use super::stdarray::StdArray;
+
+// Fortran: "        program hello", in columns 7 and on, or after a tab.
+static FORTRAN_PROGRAM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?: {6,}|\t)\s*program\b").unwrap()
+});
+
+// Pascal: "program main(output);".
+static PASCAL_PROGRAM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^\s*program\b").unwrap()
+});
+
+// Madlen: " program: ,name,". Module main is not an entry:
+// the loader starts Madlen programs at program only.
+static MADLEN_MODULE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?im)^\s*program\s*:\s*,\s*name\s*,").unwrap()
+});
+
+// БЕМШ: "main    старт   512".
+static BEMSH_MODULE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?im)^(program|main)\s+старт\b").unwrap()
+});
+
+// B and C: "main() {", "void program()".
+static FUNCTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^(?:\w+\s+)*(program|main)\s*\(").unwrap()
+});
+
+// Names of entries in ITM code, as they appear in object code.
+const PROGRAM_WORD: u64 = 0o6062574762415500;
+const MAIN_WORD: u64 = 0o5541515600000000;
+
+//
+// Whether a Fortran source is clearly a main program: its first statement is PROGRAM.
+// Main program without PROGRAM statement is not recognized;
+// then the entry is program by default anyway.
+//
+fn fortran_defines_program(text: &str) -> bool {
+    let is_comment = |line: &str| line.starts_with(['c', 'C', '*']) || line.trim().is_empty();
+    text.lines()
+        .find(|line| !is_comment(line))
+        .is_some_and(|line| FORTRAN_PROGRAM.is_match(line))
+}
+
+//
+// Whether object code contains a word, stored as 6 bytes, high byte first.
+//
+fn contains_word(bytes: &[u8], word: u64) -> bool {
+    let pattern = &word.to_be_bytes()[2..];
+    bytes.windows(6).any(|window| window == pattern)
+}
+
+//
+// Entry defined by an input file: "program", "main", or none.
+// For object files, the name is searched in the object code, and may be missed
+// or found in a module which only calls it; return Ok(None) when not found.
+//
+pub fn defined_entry(file: &str) -> Result<Option<String>, BesmcError> {
+    let Some(language) = Language::from_file(file) else {
+        return Ok(None);
+    };
+    if language == Language::Object {
+        let Ok(bytes) = fs::read(file) else {
+            return Ok(None);
+        };
+        let entry = if contains_word(&bytes, PROGRAM_WORD) {
+            Some("program")
+        } else if contains_word(&bytes, MAIN_WORD) {
+            Some("main")
+        } else {
+            None
+        };
+        return Ok(entry.map(str::to_string));
+    }
+    if language == Language::StdArray {
+        let array = StdArray::load(file)?;
+        let entry = array.modules.iter()
+                                 .map(|module| module.name.to_lowercase())
+                                 .find(|name| name == "program" || name == "main");
+        return Ok(entry);
+    }
+
+    let bytes = fs::read(file)
+                   .map_err(|e| BesmcError::io("read", file, e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let captured = |regex: &Regex| regex.captures(&text).map(|captures| captures[1].to_lowercase());
+    let entry = match language {
+        Language::Ftn | Language::Fortran | Language::Forex =>
+            fortran_defines_program(&text).then(|| "program".to_string()),
+        Language::Pascal | Language::PascalRe =>
+            text.lines().any(|line| PASCAL_PROGRAM.is_match(line)).then(|| "program".to_string()),
+        Language::Algol =>
+            Some("program".to_string()),
+        Language::Assem | Language::Madlen =>
+            MADLEN_MODULE.is_match(&text).then(|| "program".to_string()),
+        Language::Bemsh =>
+            captured(&BEMSH_MODULE),
+        Language::C =>
+            captured(&FUNCTION),
+        Language::B =>
+            FUNCTION.is_match(&text).then(|| "program".to_string()),
+        Language::StdArray | Language::Object =>
+            None,
+    };
+    Ok(entry)
+}
+
+//
+// Entry of the executable: the one defined by exactly one source.
+// Names found in object code are only a hint, used when no source
+// defines an entry and all objects agree.
+// Otherwise the inputs define no entry.
+//
+pub fn find_entry(files: &[String]) -> Result<String, BesmcError> {
+    let mut definitions = Vec::new();
+    let mut hints = Vec::new();
+    for file in files {
+        if let Some(entry) = defined_entry(file)? {
+            if Language::from_file(file) == Some(Language::Object) {
+                hints.push(entry);
+            } else {
+                definitions.push((file.clone(), entry));
+            }
+        }
+    }
+    match definitions.len() {
+        1 => Ok(definitions.remove(0).1),
+        0 => match hints.first() {
+            Some(hint) if hints.iter().all(|entry| entry == hint) => Ok(hint.clone()),
+            _ => Err(BesmcError::NoEntry { files: files.to_vec() }),
+        },
+        _ => Err(BesmcError::ManyEntries { definitions }),
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+use std::io;
+use std::process::ExitStatus;
+
+use super::listing::Diagnostic;
+use super::loader::ModuleSize;
+
+//
+// Everything that can go wrong while compiling and linking.
+// Each variant maps to its own process exit code, see exit_code().
+//
+#[derive(Debug)]
+pub enum BesmcError {
+    /// No input files given
+    NoInput,
+
+    /// Input file has an extension besmc does not know
+    UnknownExtension { file: String },
+
+    /// Executable file passed as input
+    ExecutableInput { file: String },
+
+    /// Option -o names one file, but there are many outputs
+    OutputForManyFiles { output: String },
+
+    /// More object files than there are perso disks
+    TooManyObjects { file: String },
+
+    /// More libraries given by -l than there are disks for them
+    TooManyLibraries { count: usize, limit: usize },
+
+    /// External tool is not installed or not on $PATH
+    ToolMissing { tool: String },
+
+    /// External tool could not be started for another reason
+    ToolExec { tool: String, error: io::Error },
+
+    /// External tool ran, but returned nonzero status
+    ToolFailed { tool: String, file: Option<String>, status: ExitStatus },
+
+    /// Compiler or loader reported errors in the listing
+    CompilationFailed { listing: String, diagnostics: Vec<Diagnostic> },
+
+    /// BESM-6 C header files are not installed
+    MissingHeaders { probed: Vec<String> },
+
+    /// BESM-6 C runtime library is not installed
+    MissingLibc { probed: Vec<String> },
+
+    /// Executable is larger than allowed by --max-size
+    TooLarge { output: String, words: u32, limit: u32, largest: Vec<ModuleSize> },
+
+    /// Length of the executable is not in the listing, so --max-size cannot be checked
+    UnknownSize { output: String, listing: String },
+
+    /// Library given by -l is not found
+    LibraryNotFound { name: String, probed: Vec<String> },
+
+    /// Operation cannot be done with the tools at hand
+    Unsupported { what: String, reason: String },
+
+    /// Standard array (.std) is malformed
+    BadStdArray { file: String, line: usize, message: String },
+
+    /// No input defines an entry, and the loader found no program
+    NoEntry { files: Vec<String> },
+
+    /// Several inputs define an entry: (file, entry)
+    ManyEntries { definitions: Vec<(String, String)> },
+
+    /// Some programs of the test suite failed
+    TestsFailed { failed: usize, total: usize },
+
+    /// Project manifest (besmc.toml) is malformed
+    BadManifest { file: String, message: String },
+
+    /// Object library has no module with this name
+    ModuleNotFound { library: String, module: String, modules: Vec<String> },
+
+    /// Catalog of messages cannot be parsed
+    BadPatterns { file: String, message: String },
+
+    /// Failed to read or write a file
+    Io { action: &'static str, path: String, error: io::Error },
+}
+
+impl BesmcError {
+    //
+    // Failure to start an external tool.
+    // Distinguish a missing tool from other errors.
+    //
+    pub fn spawn(tool: &str, error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::NotFound {
+            BesmcError::ToolMissing { tool: tool.to_string() }
+        } else {
+            BesmcError::ToolExec { tool: tool.to_string(), error }
+        }
+    }
+
+    //
+    // Failure of file operation, like "open" or "write".
+    //
+    pub fn io(action: &'static str, path: &str, error: io::Error) -> Self {
+        BesmcError::Io { action, path: path.to_string(), error }
+    }
+
+    //
+    // Messages found in the listing, if any.
+    //
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            BesmcError::CompilationFailed { diagnostics, .. } => diagnostics,
+            _ => &[],
+        }
+    }
+
+    //
+    // Process exit code for this error.
+    // Code 2 is skipped: clap uses it for command line errors.
+    //
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            BesmcError::CompilationFailed { .. }  => 1,
+            BesmcError::ToolFailed { .. }         => 3,
+            BesmcError::ToolMissing { .. }        => 4,
+            BesmcError::ToolExec { .. }           => 5,
+            BesmcError::MissingHeaders { .. }     => 6,
+            BesmcError::MissingLibc { .. }        => 7,
+            BesmcError::UnknownExtension { .. }   => 8,
+            BesmcError::ExecutableInput { .. }    => 9,
+            BesmcError::TooManyObjects { .. }     => 10,
+            BesmcError::NoInput                   => 11,
+            BesmcError::Io { .. }                 => 12,
+            BesmcError::OutputForManyFiles { .. } => 13,
+            BesmcError::BadPatterns { .. }        => 14,
+            BesmcError::TooLarge { .. }           => 15,
+            BesmcError::LibraryNotFound { .. }    => 16,
+            BesmcError::Unsupported { .. }        => 17,
+            BesmcError::BadStdArray { .. }        => 18,
+            BesmcError::NoEntry { .. }            => 19,
+            BesmcError::ManyEntries { .. }        => 20,
+            BesmcError::TestsFailed { .. }        => 21,
+            BesmcError::BadManifest { .. }        => 22,
+            BesmcError::ModuleNotFound { .. }     => 23,
+            BesmcError::TooManyLibraries { .. }   => 24,
+            BesmcError::UnknownSize { .. }        => 25,
+        }
+    }
+}
+
+impl fmt::Display for BesmcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BesmcError::NoInput =>
+                write!(f, "No input files"),
+            BesmcError::UnknownExtension { file } =>
+                write!(f, "Unknown file extension: {}", file),
+            BesmcError::ExecutableInput { file } =>
+                write!(f, "Cannot process executable file: {}", file),
+            BesmcError::OutputForManyFiles { output } =>
+                write!(f, "Cannot write many object files to {}: not a directory", output),
+            BesmcError::TooManyObjects { file } =>
+                write!(f, "Cannot process {}: too many object files", file),
+            BesmcError::TooManyLibraries { count, limit } =>
+                write!(f, "Cannot link {} libraries given by -l: at most {} are allowed", count, limit),
+            BesmcError::ToolMissing { tool } =>
+                write!(f, "Failed to execute {}: command not found", tool),
+            BesmcError::ToolExec { tool, error } =>
+                write!(f, "Failed to execute {}: {}", tool, error),
+            BesmcError::ToolFailed { tool, file: Some(file), status } =>
+                write!(f, "{} failed on {} with status: {}", tool, file, status),
+            BesmcError::ToolFailed { tool, file: None, status } =>
+                write!(f, "{} failed with status: {}", tool, status),
+            BesmcError::CompilationFailed { listing, .. } =>
+                write!(f, "---\nCompilation failed!\nSee details in {}", listing),
+            BesmcError::MissingHeaders { probed } =>
+                write!(f, "BESM-6 C headers not found. Looked in:\n  {}", probed.join("\n  ")),
+            BesmcError::MissingLibc { probed } =>
+                write!(f, "BESM-6 libc.bin not found. Looked in:\n  {}", probed.join("\n  ")),
+            BesmcError::TooLarge { output, words, limit, largest } => {
+                write!(f, "{} is too large: {} words ({:o} octal), limit is {} words ({:o} octal)",
+                       output, words, words, limit, limit)?;
+                if !largest.is_empty() {
+                    write!(f, "\nLargest modules:")?;
+                    for module in largest {
+                        write!(f, "\n  {:<8} {:>6} words ({:o} octal)", module.name, module.words, module.words)?;
+                    }
+                }
+                Ok(())
+            }
+            BesmcError::UnknownSize { output, listing } =>
+                write!(f, "Cannot check --max-size of {}: length of the program not found in {}", output, listing),
+            BesmcError::LibraryNotFound { name, probed } =>
+                write!(f, "Library -l{} not found. Looked in:\n  {}", name, probed.join("\n  ")),
+            BesmcError::Unsupported { what, reason } =>
+                write!(f, "Cannot {}: {}", what, reason),
+            BesmcError::BadStdArray { file, line, message } =>
+                write!(f, "{}:{}: bad standard array: {}", file, line, message),
+            BesmcError::NoEntry { files } =>
+                write!(f, "No input defines an entry:\n  {}\nUse -e NAME to give one", files.join("\n  ")),
+            BesmcError::ManyEntries { definitions } => {
+                write!(f, "Several inputs define an entry:")?;
+                for (file, entry) in definitions {
+                    write!(f, "\n  {}: {}", file, entry)?;
+                }
+                write!(f, "\nUse -e NAME to choose one")
+            }
+            BesmcError::TestsFailed { failed, total } =>
+                write!(f, "{} of {} programs failed", failed, total),
+            BesmcError::BadManifest { file, message } =>
+                write!(f, "Bad project manifest {}: {}", file, message),
+            BesmcError::ModuleNotFound { library, module, modules } =>
+                write!(f, "Module {} not found in {}. Modules are:\n  {}", module, library, modules.join("\n  ")),
+            BesmcError::BadPatterns { file, message } =>
+                write!(f, "Bad catalog of messages {}: {}", file, message),
+            BesmcError::Io { action, path, error } =>
+                write!(f, "Failed to {} {}: {}", action, path, error),
+        }
+    }
+}
+
+impl std::error::Error for BesmcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BesmcError::ToolExec { error, .. } | BesmcError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use super::{CompilerOptions, BuildArtifacts, BesmcError};
+use super::compiler::{compile_files, number_of_jobs, run_jobs};
+use super::language::Language;
+use super::plan::ToolCommand;
+use super::workdir::WorkDir;
+
+//
+// Outcome of one program of the test suite.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    /// Output matches the expected one
+    Passed,
+
+    /// Expected output is written from the actual one
+    Blessed,
+
+    /// Something went wrong: build, run or output
+    Failed(String),
+}
+
+//
+// Program of the test suite, with its outcome.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResult {
+    pub program: String,
+    pub outcome: TestOutcome,
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            TestOutcome::Passed          => write!(f, "ok      {}", self.program),
+            TestOutcome::Blessed         => write!(f, "blessed {}", self.program),
+            TestOutcome::Failed(message) => write!(f, "FAIL    {}: {}", self.program, message),
+        }
+    }
+}
+
+//
+// Programs of the test suite: sources and standard arrays in the directory,
+// sorted by name. Each one is built alone.
+//
+pub fn find_programs(dir: &str) -> Result<Vec<String>, BesmcError> {
+    let entries = fs::read_dir(dir)
+                     .map_err(|e| BesmcError::io("read directory", dir, e))?;
+    let mut programs = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| BesmcError::io("read directory", dir, e))?.path();
+        let name = path.to_string_lossy().into_owned();
+        match Language::from_file(&name) {
+            Some(Language::Object) | None => {}
+            Some(_) => programs.push(name),
+        }
+    }
+    programs.sort();
+    Ok(programs)
+}
+
+//
+// Explain where two outputs differ: the first line which is not the same.
+//
+pub fn compare_output(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => {
+                return Some(format!("output differs at line {}\n    expected: {}\n    actual:   {}",
+                                    line, e.unwrap_or("<end of output>"), a.unwrap_or("<end of output>")));
+            }
+        }
+    }
+}
+
+//
+// Input of the program: NAME.input, when present.
+//
+fn input_file(program: &str) -> Option<String> {
+    let input = Path::new(program).with_extension("input");
+    input.is_file().then(|| input.to_string_lossy().into_owned())
+}
+
+//
+// Build one program in a work directory, run it, and check its output.
+// Input is taken from NAME.input, when present; output is compared with NAME.expected.
+//
+fn test_program(program: &str, bless: bool, options: &CompilerOptions) -> Result<TestOutcome, BesmcError> {
+    let workdir = WorkDir::create(options.save_temps)?;
+    let name = Path::new(program).with_extension("exe");
+    let executable = workdir.file(&name.file_name().unwrap_or_default().to_string_lossy());
+    let build_options = CompilerOptions {
+        files: vec![program.to_string()],
+        output_file: Some(executable.clone()),
+        stop_at_object: false,
+        jobs: None,
+        command: None,
+        ..options.clone()
+    };
+    if let Err(err) = compile_files(&build_options) {
+        let reason = match err.diagnostics().first() {
+            Some(diagnostic) => diagnostic.to_string(),
+            None => err.to_string(),
+        };
+
+        // Keep the listing, which goes away with the work directory.
+        if let BesmcError::CompilationFailed { listing, .. } = &err {
+            let kept = Path::new(program).with_extension("lst").to_string_lossy().into_owned();
+            fs::copy(listing, &kept)
+               .map_err(|e| BesmcError::io("copy", listing, e))?;
+            return Ok(TestOutcome::Failed(format!("build failed: {}, see {}", reason, kept)));
+        }
+        return Ok(TestOutcome::Failed(format!("build failed: {}", reason)));
+    }
+
+    let input = input_file(program);
+    let output = ToolCommand::new(&executable, &[], program).output_with_input(input.as_deref(), options.verbose)?;
+    let actual = String::from_utf8_lossy(&output.stdout);
+
+    if !output.status.success() {
+        return Ok(TestOutcome::Failed(format!("program failed with status: {}", output.status)));
+    }
+    let expected_file = Path::new(program).with_extension("expected").to_string_lossy().into_owned();
+    if bless {
+        fs::write(&expected_file, actual.as_bytes())
+           .map_err(|e| BesmcError::io("write", &expected_file, e))?;
+        return Ok(TestOutcome::Blessed);
+    }
+    let Ok(expected) = fs::read(&expected_file) else {
+        return Ok(TestOutcome::Failed(format!("{} not found, run with --bless to create it", expected_file)));
+    };
+    match compare_output(&String::from_utf8_lossy(&expected), &actual) {
+        Some(difference) => Ok(TestOutcome::Failed(difference)),
+        None => Ok(TestOutcome::Passed),
+    }
+}
+
+//
+// Run all programs of the directory, up to -j at once, and print the results
+// in order of names, with a summary. Fail when any program fails.
+//
+pub fn run_tests(dir: &str, bless: bool, options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    let programs = find_programs(dir)?;
+    if programs.is_empty() {
+        return Err(BesmcError::NoInput);
+    }
+
+    // Dry run shows how every program would be built and run.
+    if options.dry_run {
+        let dir = std::env::current_dir()
+                           .map_err(|e| BesmcError::io("get current directory", ".", e))?;
+        for program in &programs {
+            let executable = Path::new(program).with_extension("exe").to_string_lossy().into_owned();
+            let build_options = CompilerOptions {
+                files: vec![program.clone()],
+                output_file: Some(executable.clone()),
+                stop_at_object: false,
+                command: None,
+                ..options.clone()
+            };
+            compile_files(&build_options)?;
+            let input = input_file(program);
+            println!("{}", ToolCommand::new(&executable, &[], program).command_line(&dir, input.as_deref(), None));
+        }
+        return Ok(BuildArtifacts::default());
+    }
+    let results = run_jobs(&programs, number_of_jobs(options), |program| test_program(program, bless, options));
+
+    let mut failed = 0;
+    for (program, result) in programs.iter().zip(results) {
+        let outcome = result?;
+        if matches!(outcome, TestOutcome::Failed(_)) {
+            failed += 1;
+        }
+        println!("{}", TestResult { program: program.clone(), outcome });
+    }
+    let total = programs.len();
+    if bless {
+        println!("{} programs, {} blessed, {} failed", total, total - failed, failed);
+    } else {
+        println!("{} programs, {} passed, {} failed", total, total - failed, failed);
+    }
+    if failed > 0 {
+        return Err(BesmcError::TestsFailed { failed, total });
+    }
+    Ok(BuildArtifacts::default())
+}
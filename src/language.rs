@@ -0,0 +1,113 @@
+use std::fmt;
+use std::path::Path;
+
+//
+// Kinds of input files besmc knows.
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Ftn,        // *.ftn     - Fortran-ГДP
+    Fortran,    // *.fortran - Fortran Dubna
+    Forex,      // *.forex   - Forex
+    Algol,      // *.algol   - Algol-ГДP
+    Pascal,     // *.pascal  - Pascal
+    PascalRe,   // *.pas     - Pascal-re
+    Assem,      // *.assem   - Assembler Madlen
+    Madlen,     // *.madlen  - Assembler Madlen-3.5
+    Bemsh,      // *.bemsh   - Assembler БЕМШ
+    B,          // *.b       - B language
+    C,          // *.c       - C language
+    StdArray,   // *.std     - Standard array
+    Object,     // *.obj     - Object library
+}
+
+impl Language {
+    //
+    // Find language by file extension.
+    //
+    pub fn from_file(filename: &str) -> Option<Language> {
+        let ext = Path::new(filename).extension()?;
+        Language::from_extension(&ext.to_string_lossy())
+    }
+
+    //
+    // Find language by extension, without the dot.
+    //
+    pub fn from_extension(ext: &str) -> Option<Language> {
+        match ext {
+            "ftn"     => Some(Language::Ftn),
+            "fortran" => Some(Language::Fortran),
+            "forex"   => Some(Language::Forex),
+            "algol"   => Some(Language::Algol),
+            "pascal"  => Some(Language::Pascal),
+            "pas"     => Some(Language::PascalRe),
+            "assem"   => Some(Language::Assem),
+            "madlen"  => Some(Language::Madlen),
+            "bemsh"   => Some(Language::Bemsh),
+            "b"       => Some(Language::B),
+            "c"       => Some(Language::C),
+            "std"     => Some(Language::StdArray),
+            "obj"     => Some(Language::Object),
+            _         => None,
+        }
+    }
+
+    //
+    // Human readable name.
+    //
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::Ftn      => "Fortran-ГДP",
+            Language::Fortran  => "Fortran Dubna",
+            Language::Forex    => "Forex",
+            Language::Algol    => "Algol-ГДP",
+            Language::Pascal   => "Pascal",
+            Language::PascalRe => "Pascal-re",
+            Language::Assem    => "Madlen",
+            Language::Madlen   => "Madlen-3.5",
+            Language::Bemsh    => "БЕМШ",
+            Language::B        => "B",
+            Language::C        => "C",
+            Language::StdArray => "Standard array",
+            Language::Object   => "Object library",
+        }
+    }
+
+    //
+    // Regular expression for the title line, which the compiler
+    // prints to the listing when it starts.
+    // Note that listings use Latin letters in place of similar Cyrillic ones.
+    //
+    pub fn banner(self) -> Option<&'static str> {
+        match self {
+            Language::Ftn      => Some(r"Ф O P T P A H - Г Д P"),
+            Language::Fortran  => Some(r"^ *Ф O P T P A H *$"),
+            Language::Forex    => Some(r"F O R E X"),
+            Language::Algol    => Some(r"A L G O L - Г Д P"),
+            Language::Pascal   => Some(r"PASCAL COMPILER"),
+            Language::Assem    => Some(r"ABTOKOД +MADLEN"),
+            Language::Madlen   => Some(r"MADLEN-3\.5"),
+            Language::C        => Some(r"MADLEN-3\.5"),
+            Language::Bemsh    => Some(r"MAKPO-БEMШ"),
+            Language::PascalRe => Some(r"^\*-CM-\*"),
+            Language::StdArray => Some(r"^\*-CM-\*"),
+            Language::B        => None,
+            Language::Object   => None,
+        }
+    }
+
+    //
+    // Whether the compiler echoes source lines to the listing,
+    // so that messages can be mapped back to lines of the input file.
+    // Sources translated by external passes are echoed in translated form.
+    //
+    pub fn echoes_source(self) -> bool {
+        !matches!(self, Language::PascalRe | Language::C | Language::StdArray | Language::Object)
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,317 @@
+//
+// BESM-6 compiler frontend as a library.
+//
+// The `besmc` command is a thin wrapper over this crate: it parses
+// the command line into `CompilerOptions` and runs a `Build`.
+// Build scripts and test harnesses can do the same directly:
+//
+//      let options = CompilerOptions {
+//          files: vec!["hello.pascal".to_string()],
+//          ..Default::default()
+//      };
+//      let artifacts = Build::new(options).run()?;
+//
+use clap::{Parser, Subcommand};
+
+mod catalog;
+mod compiler;
+mod diagnostics;
+mod disasm;
+mod entry;
+mod error;
+mod golden;
+mod language;
+mod linkmap;
+mod listing;
+mod loader;
+mod messages;
+mod objlib;
+mod plan;
+mod project;
+mod run;
+mod stdarray;
+mod warnings;
+mod workdir;
+pub use catalog::{Catalog, Pattern};
+pub use compiler::{compile_files, plan_files, plan_jobs};
+pub use diagnostics::{DiagnosticsFormat, format_diagnostics};
+pub use disasm::{ModuleLayout, ModuleSymbol, disassemble, disassemble_instruction, disassemble_word, module_layout, run_disasm};
+pub use entry::{defined_entry, find_entry};
+pub use error::BesmcError;
+pub use golden::{TestOutcome, TestResult, compare_output, find_programs, run_tests};
+pub use language::Language;
+pub use loader::{LinkSummary, ModuleSize, ReportFormat, format_size_report, parse_loader_output};
+pub use linkmap::{LinkMap, MapLibrary, MapModule, build_link_map};
+pub use listing::{Diagnostic, Severity, Unit, scan_listing, scan_listing_with};
+pub use warnings::WarningOption;
+pub use messages::{Messages, normalize, render, translate};
+pub use objlib::{LibAction, LibraryContents, LibraryModule, library_contents, list_library, run_convert, run_lib};
+pub use run::run_program;
+pub use plan::{Emit, Library, Plan, ScriptPart, ToolCommand};
+pub use project::{MANIFEST, Project, Target, TargetKind, build_project};
+pub use stdarray::{StdArray, StdModule, StdRecord};
+
+// Error type returned by Build::run().
+pub type BuildError = BesmcError;
+
+#[cfg(test)]
+mod test;
+
+// Data structure to hold all parsed compiler options and files
+#[derive(Debug, Parser, Default, Clone)]
+#[command(
+    about = "BESM-6 compiler frontend",
+    disable_help_flag = false,
+    arg_required_else_help = true
+)]
+pub struct CompilerOptions {
+    /// Output file name
+    #[arg(short = 'o', long = "output", value_name = "FILE")]
+    pub output_file: Option<String>,
+
+    /// Compile only to object files
+    #[arg(short = 'c', long = "compile")]
+    pub stop_at_object: bool,
+
+    /// Entry of the executable, like program or main (default: found in the inputs)
+    #[arg(short = 'e', long = "entry", value_name = "NAME")]
+    pub entry: Option<String>,
+
+    /// With -c, write an object library (obj) or a standard array (std)
+    #[arg(long = "emit", value_name = "FORMAT", default_value = "obj", requires = "stop_at_object")]
+    pub emit: Emit,
+
+    /// Keep intermediate files
+    #[arg(short = 't', long = "save-temps", global = true)]
+    pub save_temps: bool,
+
+    /// Show every external command as it is run, with its status and time
+    #[arg(short = 'v', long = "verbose", global = true)]
+    pub verbose: bool,
+
+    /// Print the Dubna job and commands, without running them (also -###)
+    #[arg(long = "dry-run", global = true)]
+    pub dry_run: bool,
+
+    /// Format of compiler messages: text, json or sarif
+    #[arg(long = "diagnostics-format", value_name = "FORMAT", default_value = "text", global = true)]
+    pub diagnostics_format: DiagnosticsFormat,
+
+    /// Warning options: -Werror makes warnings fail the build
+    #[arg(short = 'W', value_name = "OPTION", global = true)]
+    pub warning_options: Vec<WarningOption>,
+
+    /// Do not show warnings
+    #[arg(short = 'w', global = true)]
+    pub no_warnings: bool,
+
+    /// Extend or override the catalog of compiler messages by a TOML file
+    #[arg(long = "patterns", value_name = "FILE", global = true)]
+    pub patterns: Option<String>,
+
+    /// Show compiler messages as in the listing, in proper Cyrillic, or in English
+    #[arg(long = "messages", value_name = "LANG", default_value = "ru", global = true)]
+    pub messages: Messages,
+
+    /// Rewrite the listing file the same way as compiler messages
+    #[arg(long = "translate-listing")]
+    pub translate_listing: bool,
+
+    /// Add directory to the search path of libraries given by -l
+    #[arg(short = 'L', value_name = "DIR")]
+    pub library_dirs: Vec<String>,
+
+    /// Link with object library libNAME.obj, found in -L directories or in share/besm6/lib
+    #[arg(short = 'l', value_name = "NAME")]
+    pub libraries: Vec<String>,
+
+    /// Write a map of linked modules and libraries to FILE
+    #[arg(long = "map", value_name = "FILE")]
+    pub map: Option<String>,
+
+    /// Fail when the executable is longer than WORDS (decimal, or octal like 0o100000 or 100000B)
+    #[arg(long = "max-size", value_name = "WORDS", value_parser = loader::parse_words)]
+    pub max_size: Option<u32>,
+
+    /// Print sizes of modules, as a table or as JSON
+    #[arg(long = "size-report", value_name = "FORMAT", num_args = 0..=1, require_equals = true, default_missing_value = "text")]
+    pub size_report: Option<ReportFormat>,
+
+    /// With -c, create one object file per source, named after it
+    #[arg(long = "split-objects")]
+    pub split_objects: bool,
+
+    /// With -c, compile each source by a separate Dubna job, running N jobs at once (0 = one per CPU)
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Subcommand, instead of compiling files
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    // Input files
+    #[arg(
+        value_name = "FILES",
+        help = "Input sources and object files:\n\
+                 *.ftn     - Fortran-ГДP\n\
+                 *.fortran - Fortran Dubna\n\
+                 *.forex   - Forex\n\
+                 *.algol   - Algol-ГДP\n\
+                 *.pascal  - Pascal\n\
+                 *.pas     - Pascal-re\n\
+                 *.assem   - Assembler Madlen\n\
+                 *.madlen  - Assembler Madlen-3.5\n\
+                 *.bemsh   - Assembler БЕМШ\n\
+                 *.c       - C language\n\
+                 *.obj     - Object Library (*perso)\n\
+                 *.std     - Standard array (*punch)"
+    )]
+    pub files: Vec<String>,
+}
+
+//
+// Subcommands of besmc, for jobs other than compiling.
+//
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Manage object libraries: list, add, replace or extract modules
+    Lib {
+        #[command(subcommand)]
+        action: LibAction,
+    },
+
+    /// Show instructions and text constants of a standard array (.std) or object library (.obj)
+    Disasm {
+        /// Standard array (.std) or object library (.obj)
+        file: String,
+    },
+
+    /// Convert an object library (.obj) to a standard array (.std), or back
+    Convert {
+        /// Input file
+        input: String,
+
+        /// Output file
+        output: String,
+    },
+
+    /// Build the executable and run it through dubna
+    Run {
+        /// Give the program input from FILE
+        #[arg(short = 'i', long = "input", value_name = "FILE")]
+        input: Option<String>,
+
+        /// Keep the executable, named as by a usual build
+        #[arg(short = 'k', long = "keep")]
+        keep: bool,
+
+        /// Input sources and object files
+        #[arg(value_name = "FILES", required = true)]
+        files: Vec<String>,
+    },
+
+    /// Build and run every program in DIR, and compare its output with NAME.expected
+    Test {
+        /// Write the output to NAME.expected, instead of comparing
+        #[arg(long = "bless")]
+        bless: bool,
+
+        /// Directory with programs, their input (NAME.input) and expected output
+        #[arg(value_name = "DIR")]
+        dir: String,
+    },
+
+    /// Build a target of the project manifest besmc.toml, or all targets, dependencies first
+    Build {
+        /// Read the manifest from FILE
+        #[arg(long = "manifest", value_name = "FILE", default_value = project::MANIFEST)]
+        manifest: String,
+
+        /// Target to build (default: all)
+        #[arg(value_name = "TARGET")]
+        target: Option<String>,
+    },
+}
+
+//
+// Results of a successful build.
+//
+#[derive(Debug, Default, Clone)]
+pub struct BuildArtifacts {
+    /// Executable file (.exe), when linking
+    pub executable: Option<String>,
+
+    /// Object library (.obj), when compiling with -c
+    pub object: Option<String>,
+
+    /// Object files, one per source, when compiling with --split-objects
+    pub objects: Vec<String>,
+
+    /// Listing produced by Dubna (of the first source, with --split-objects)
+    pub listing: String,
+
+    /// Library length as reported by the loader, e.g. "002 17"
+    pub library_length: Option<String>,
+
+    /// Sizes of modules reported by compilers and loader
+    pub link: LinkSummary,
+
+    /// Messages found in the listing, which did not fail the build
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Non-fatal problems noticed during the build
+    pub warnings: Vec<String>,
+
+    /// Exit status of the program, with besmc run
+    pub status: Option<i32>,
+}
+
+//
+// One invocation of the compiler: a set of options and the files they apply to.
+//
+#[derive(Debug, Clone)]
+pub struct Build {
+    options: CompilerOptions,
+}
+
+impl Build {
+    pub fn new(options: CompilerOptions) -> Self {
+        Build { options }
+    }
+
+    pub fn options(&self) -> &CompilerOptions {
+        &self.options
+    }
+
+    //
+    // Compile and, unless -c is given, link the input files.
+    // With a subcommand, run it instead.
+    //
+    pub fn run(&self) -> Result<BuildArtifacts, BuildError> {
+        match &self.options.command {
+            Some(Command::Lib { action }) => run_lib(action, &self.options),
+            Some(Command::Disasm { file }) => run_disasm(file, &self.options),
+            Some(Command::Convert { input, output }) => run_convert(input, output, &self.options),
+            Some(Command::Run { input, keep, files }) => run_program(files, input.as_deref(), *keep, &self.options),
+            Some(Command::Test { bless, dir }) => run_tests(dir, *bless, &self.options),
+            Some(Command::Build { manifest, target }) => build_project(manifest, target.as_deref(), &self.options),
+            None => compile_files(&self.options),
+        }
+    }
+
+    //
+    // Decide what a single Dubna job would do, without running anything.
+    // Useful to inspect the generated script.
+    //
+    pub fn plan(&self) -> Result<Plan, BuildError> {
+        plan_files(&self.options)
+    }
+
+    //
+    // Decide what all Dubna jobs of the build would do, in order,
+    // as --dry-run shows them.
+    //
+    pub fn plans(&self) -> Result<Vec<Plan>, BuildError> {
+        plan_jobs(&self.options)
+    }
+}
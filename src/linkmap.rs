@@ -0,0 +1,225 @@
+use std::fmt;
+use std::fs;
+use std::sync::LazyLock;
+use regex::Regex;
+
+use super::{CompilerOptions, BesmcError};
+use super::loader;
+use super::objlib::{library_modules, punch_library};
+use super::plan::Plan;
+use super::stdarray::StdArray;
+use super::workdir::WorkDir;
+
+// Object library loaded from perso disk: " LIBRARY OT  14/04/25 00.28.36"
+pub(crate) static LIBRARY_LOADED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^ *LIBRARY OT +(.*\S)").unwrap()
+});
+
+//
+// Library searched by the loader.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapLibrary {
+    /// Directive of Dubna script, like "*perso:40"
+    pub directive: String,
+
+    /// Input file or system library
+    pub origin: String,
+
+    /// Date of the object library, when the loader reported it
+    pub created: Option<String>,
+}
+
+//
+// Module placed into the executable.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapModule {
+    pub name: String,
+
+    /// Load address, when reported
+    pub address: Option<u32>,
+
+    /// Length in words
+    pub words: u32,
+
+    /// Source or library the module came from
+    pub origin: String,
+}
+
+//
+// What got linked into the executable, and where.
+//
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkMap {
+    pub output: String,
+    pub libraries: Vec<MapLibrary>,
+    pub modules: Vec<MapModule>,
+
+    /// Length of executable, as printed by the loader
+    pub library_length: Option<String>,
+}
+
+//
+// Part of the listing, which a line belongs to.
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// Dubna messages before the first compiler
+    Start,
+
+    /// Output of the compiler of the unit with this index
+    Compiler(usize),
+
+    /// Output of the loader: libraries loaded and length of the executable
+    Loader,
+}
+
+//
+// Collect the link map from the listing of a job, and the punched object libraries
+// it loaded by *call perso, as (input file, standard array).
+// The listing is split into sections: a compiler section starts at the banner
+// of its unit, and the loader section starts at the first library it loads,
+// or at the length of the executable. Modules of compiled sources are attributed
+// to the unit of the section they are reported in. Modules of object libraries
+// are taken from the headers of their standard arrays.
+// Addresses are known only where the listing prints them, like HAM= of БЕМШ.
+// Fail when a module cannot be attributed to an input, or no module is found.
+//
+pub fn build_link_map(text: &str, plan: &Plan, objects: &[(String, StdArray)]) -> Result<LinkMap, BesmcError> {
+    let banners: Vec<Option<Regex>> = plan.units.iter()
+        .map(|unit| unit.language.banner().map(|p| Regex::new(p).expect("Invalid regex pattern")))
+        .collect();
+    let mut map = LinkMap {
+        output: plan.output.clone(),
+        libraries: plan.libraries.iter().map(|library| MapLibrary {
+            directive: library.directive.clone(),
+            origin: library.origin.clone(),
+            created: None,
+        }).collect(),
+        modules: Vec::new(),
+        library_length: loader::parse_loader_output(text).library_length,
+    };
+    let unattributed = |what: String| BesmcError::Unsupported {
+        what: format!("create link map of {}", plan.output),
+        reason: what,
+    };
+
+    // Object libraries are loaded in order of *call perso.
+    let persos: Vec<usize> = (0..map.libraries.len())
+        .filter(|&i| map.libraries[i].directive.starts_with("*perso"))
+        .collect();
+    let mut section = Section::Start;
+    let mut next_unit = 0;
+    let mut next_perso = persos.iter();
+    for line in text.lines() {
+        let started = match section {
+            Section::Loader => None,
+            _ => (next_unit..plan.units.len()).find(|&u| {
+                banners[u].as_ref().is_some_and(|banner| banner.is_match(line))
+            }),
+        };
+        if let Some(u) = started {
+            next_unit = u + 1;
+            section = Section::Compiler(u);
+        } else if let Some(captures) = LIBRARY_LOADED.captures(line) {
+            section = Section::Loader;
+            if let Some(&index) = next_perso.next() {
+                map.libraries[index].created = Some(captures[1].to_string());
+            }
+        } else if loader::LIBRARY_LENGTH.is_match(line) {
+            section = Section::Loader;
+        } else if let Some(module) = loader::parse_module_line(line) {
+            let Section::Compiler(u) = section else {
+                return Err(unattributed(format!("module {} is not reported by a compiler", module.name)));
+            };
+            map.modules.push(MapModule {
+                name: module.name,
+                address: module.address,
+                words: module.words,
+                origin: plan.units[u].file.clone(),
+            });
+        }
+    }
+
+    for &index in &persos {
+        let origin = &map.libraries[index].origin;
+        let Some((_, array)) = objects.iter().find(|(file, _)| file == origin) else {
+            return Err(unattributed(format!("modules of {} are not known", origin)));
+        };
+        for module in library_modules(array) {
+            map.modules.push(MapModule {
+                name: module.name,
+                address: None,
+                words: module.words,
+                origin: origin.clone(),
+            });
+        }
+    }
+    if map.modules.is_empty() {
+        return Err(unattributed("no modules found".to_string()));
+    }
+    Ok(map)
+}
+
+//
+// Punch object libraries, which the job loads by *call perso, to find their modules.
+//
+pub(crate) fn punch_objects(plan: &Plan, options: &CompilerOptions) -> Result<Vec<(String, StdArray)>, BesmcError> {
+    let options = CompilerOptions { map: None, ..options.clone() };
+    let mut objects = Vec::new();
+    for library in plan.libraries.iter().filter(|library| library.directive.starts_with("*perso")) {
+        let workdir = WorkDir::create(options.save_temps)?;
+        let (array, _) = punch_library(&library.origin, &workdir, &options)?;
+        objects.push((library.origin.clone(), array));
+    }
+    Ok(objects)
+}
+
+impl LinkMap {
+    //
+    // Write the map to a text file.
+    //
+    pub fn write(&self, file: &str) -> Result<(), BesmcError> {
+        fs::write(file, self.to_string())
+            .map_err(|e| BesmcError::io("write", file, e))
+    }
+}
+
+//
+// Show the map as text, for people.
+//
+impl fmt::Display for LinkMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Link map of {}", self.output)?;
+        writeln!(f)?;
+        writeln!(f, "Libraries, in order of search:")?;
+        for library in &self.libraries {
+            write!(f, "  {:<12} {}", library.directive, library.origin)?;
+            if let Some(created) = &library.created {
+                write!(f, ", created {}", created)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+
+        let width = self.modules.iter()
+                                .map(|module| module.name.chars().count())
+                                .max()
+                                .unwrap_or(0)
+                                .max("Module".len());
+        writeln!(f, "Modules:")?;
+        writeln!(f, "  {:<width$}  {:>7}  {:>7}  {:>7}  Origin", "Module", "Address", "Octal", "Decimal")?;
+        for module in &self.modules {
+            let address = module.address.map_or("-".to_string(), |a| format!("{:05o}", a));
+            writeln!(f, "  {:<width$}  {:>7}  {:>7o}  {:>7}  {}", module.name, address, module.words, module.words, module.origin)?;
+        }
+        let total: u32 = self.modules.iter().map(|module| module.words).sum();
+        writeln!(f, "  {:<width$}  {:>7}  {:>7o}  {:>7}", "Total", "", total, total)?;
+        if let Some(length) = &self.library_length {
+            writeln!(f)?;
+            writeln!(f, "Library length: {}", length)?;
+        }
+        Ok(())
+    }
+}
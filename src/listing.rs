@@ -0,0 +1,312 @@
+use std::fmt;
+use std::fs;
+use std::sync::LazyLock;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::BesmcError;
+use super::catalog::Catalog;
+use super::language::Language;
+
+//
+// Compiled unit: one input file of the Dubna job.
+// Units appear in the listing in the same order as in the script.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unit {
+    /// Input file, as given by user
+    pub file: String,
+
+    pub language: Language,
+}
+
+//
+// How serious a message is.
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Note    => "note",
+            Severity::Warning => "warning",
+            Severity::Error   => "error",
+        })
+    }
+}
+
+//
+// Message found in the listing, mapped back to the input file.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Input file, or the listing itself when the message
+    /// does not belong to any input file
+    pub file: String,
+
+    /// Line and column in the file, starting from 1, when known
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+
+    pub severity: Severity,
+
+    /// Message, with asterisks and extra spaces removed
+    pub message: String,
+
+    /// Language of the unit, when known
+    pub language: Option<Language>,
+
+    /// Line number in the listing, starting from 1
+    pub listing_line: usize,
+
+    /// Original text of the listing line
+    pub text: String,
+
+    /// Pattern which matched the line, and what it means
+    pub rule: String,
+    pub description: String,
+}
+
+//
+// Show as "file:line:column: severity: message", like gcc does.
+//
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, "{}:", line)?;
+            if let Some(column) = self.column {
+                write!(f, "{}:", column)?;
+            }
+        }
+        write!(f, " {}: {}", self.severity, self.message)
+    }
+}
+
+// Explicit line number in a message, like "ERROR 056 IN 00002 UNDEFINED LABEL".
+const LINE_NUMBER_PATTERN: &str = r"^ ?(?:ERROR|WARNING) \d+ IN (\d+) ";
+
+// How many source lines may be skipped in the listing, like comments.
+const LOOKAHEAD: usize = 4;
+
+static LINE_NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(LINE_NUMBER_PATTERN).unwrap());
+
+//
+// Bring a character to the form used in listings:
+// upper case, with Latin letters in place of similar Cyrillic ones.
+//
+fn fold_char(c: char) -> char {
+    let upper = c.to_uppercase().next().unwrap_or(c);
+    match upper {
+        'А' => 'A', 'В' => 'B', 'Е' => 'E', 'К' => 'K',
+        'М' => 'M', 'Н' => 'H', 'О' => 'O', 'Р' => 'P',
+        'С' => 'C', 'Т' => 'T', 'У' => 'Y', 'Х' => 'X',
+        '◇' => '$',
+        _ => upper,
+    }
+}
+
+//
+// Folded text without spaces, for comparing source lines with their echo.
+//
+fn squeeze(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(fold_char)
+        .collect()
+}
+
+//
+// Remove asterisks and extra spaces from the listing line.
+//
+fn clean_message(text: &str) -> String {
+    text.trim()
+        .trim_start_matches('*')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+//
+// Name mentioned in a message, like FOOBAR in
+// "HEOПИCAHHЫЙ ИДEHTИФИKATOP FOOBAR", B in "UNDEFINED IDENTIFIER>B<"
+// or A in "ЗHAЧEH. A HE ИCПOЛЬЗYETCЯ".
+//
+fn mentioned_name(message: &str) -> Option<String> {
+    let name = match (message.rfind('>'), message.rfind('<')) {
+        (Some(start), Some(end)) if start < end => &message[start + 1..end],
+        _ => match message.strip_prefix("ЗHAЧEH.") {
+            Some(rest) => rest.split_whitespace().next()?,
+            None => message.split_whitespace().last()?,
+        },
+    };
+    let name = name.trim_matches(|c: char| !c.is_alphanumeric());
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.chars().map(fold_char).collect())
+    }
+}
+
+//
+// Find a name as a whole word in a source line.
+// Return column, starting from 1.
+//
+fn find_word(line: &str, word: &str) -> Option<usize> {
+    let folded: Vec<char> = line.chars().map(fold_char).collect();
+    let word: Vec<char> = word.chars().collect();
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    (0..folded.len()).find(|&start| {
+        let end = start + word.len();
+        end <= folded.len()
+            && folded[start..end] == word[..]
+            && (start == 0 || !is_word_char(folded[start - 1]))
+            && (end == folded.len() || !is_word_char(folded[end]))
+    }).map(|start| start + 1)
+}
+
+//
+// Source of a unit, split into lines.
+//
+struct Source {
+    lines: Vec<String>,
+    squeezed: Vec<String>,
+
+    // Number of lines echoed so far
+    cursor: usize,
+}
+
+impl Source {
+    fn load(unit: &Unit) -> Option<Source> {
+        if !unit.language.echoes_source() {
+            return None;
+        }
+        let text = fs::read_to_string(&unit.file).ok()?;
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let squeezed = lines.iter().map(|line| squeeze(line)).collect();
+        Some(Source { lines, squeezed, cursor: 0 })
+    }
+
+    //
+    // Check whether the listing line is an echo of one of next source lines.
+    //
+    fn advance(&mut self, listing_line: &str) {
+        let echo = squeeze(listing_line);
+        let end = (self.cursor + LOOKAHEAD).min(self.lines.len());
+        for index in self.cursor..end {
+            let line = &self.squeezed[index];
+            if !line.is_empty() && echo.ends_with(line.as_str()) {
+                self.cursor = index + 1;
+                return;
+            }
+        }
+    }
+
+    //
+    // Find line and column for a message.
+    // Prefer the line which mentions the name from the message,
+    // starting from the line echoed last.
+    //
+    fn locate(&self, message: &str) -> (Option<usize>, Option<usize>) {
+        if let Some(name) = mentioned_name(message) {
+            let current = self.cursor.saturating_sub(1);
+            let order = (current..self.lines.len()).chain(0..current);
+            for index in order {
+                if let Some(column) = find_word(&self.lines[index], &name) {
+                    return (Some(index + 1), Some(column));
+                }
+            }
+        }
+        if self.cursor > 0 {
+            (Some(self.cursor), None)
+        } else {
+            (None, None)
+        }
+    }
+}
+
+//
+// Find messages in the listing text, and map them to units.
+// Units are recognized by titles of their compilers.
+// Messages outside of any unit are reported against the listing itself.
+//
+pub fn scan_listing(text: &str, units: &[Unit], listing_name: &str) -> Vec<Diagnostic> {
+    scan_listing_with(Catalog::builtin(), text, units, listing_name)
+}
+
+//
+// Find messages in the listing text, using given catalog of messages.
+//
+pub fn scan_listing_with(catalog: &Catalog, text: &str, units: &[Unit], listing_name: &str) -> Vec<Diagnostic> {
+    let banners: Vec<Option<Regex>> = units.iter()
+        .map(|unit| unit.language.banner().map(|p| Regex::new(p).expect("Invalid regex pattern")))
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut next_unit = 0;
+    let mut source: Option<Source> = None;
+    for (index, line) in text.lines().enumerate() {
+
+        // Title of the next compiler starts the next unit.
+        let started = (next_unit..units.len()).find(|&u| {
+            banners[u].as_ref().is_some_and(|banner| banner.is_match(line))
+        });
+        if let Some(u) = started {
+            current = Some(u);
+            next_unit = u + 1;
+            source = Source::load(&units[u]);
+            continue;
+        }
+
+        let Some(pattern) = catalog.classify(line, current.map(|u| units[u].language)) else {
+            if let Some(source) = source.as_mut() {
+                source.advance(line);
+            }
+            continue;
+        };
+
+        let message = clean_message(line);
+        let mut diagnostic = Diagnostic {
+            file: listing_name.to_string(),
+            line: Some(index + 1),
+            column: None,
+            severity: pattern.severity,
+            message,
+            language: None,
+            listing_line: index + 1,
+            text: line.to_string(),
+            rule: pattern.id.clone(),
+            description: pattern.description.clone(),
+        };
+        if let (Some(u), false) = (current, pattern.loader) {
+            diagnostic.file = units[u].file.clone();
+            diagnostic.language = Some(units[u].language);
+            diagnostic.line = None;
+            if !pattern.whole_unit {
+                if let Some(captures) = LINE_NUMBER.captures(line) {
+                    diagnostic.line = captures[1].parse().ok();
+                } else if let Some(source) = source.as_ref() {
+                    (diagnostic.line, diagnostic.column) = source.locate(&diagnostic.message);
+                }
+            }
+        }
+        diagnostics.push(diagnostic);
+    }
+    diagnostics
+}
+
+//
+// Find messages in the listing file.
+//
+pub fn scan_listing_file(catalog: &Catalog, listing_file: &str, units: &[Unit]) -> Result<Vec<Diagnostic>, BesmcError> {
+    let bytes = fs::read(listing_file)
+                   .map_err(|e| BesmcError::io("read", listing_file, e))?;
+    Ok(scan_listing_with(catalog, &String::from_utf8_lossy(&bytes), units, listing_file))
+}
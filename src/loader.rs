@@ -0,0 +1,235 @@
+use std::fmt::Write;
+use std::fs;
+use std::sync::LazyLock;
+use clap::ValueEnum;
+use regex::Regex;
+use serde::Serialize;
+
+use super::BesmcError;
+
+// Algol-ГДP: "PROGRAM   ДЛИHA:   16 00020B BPEMЯ:  0.00 CEK. KAPT:   4"
+static ALGOL_LENGTH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^ *(\S+) +ДЛИHA: +\d+ +([0-7]+)B").unwrap()
+});
+
+// Fortran-ГДP: " >> HELLO  <<    LENGTH: 00009 00011B   INPUT CARDS: 00005"
+static FTN_LENGTH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r">> +(\S+) +<< +LENGTH: +\d+ +([0-7]+)B").unwrap()
+});
+
+// Forex and Fortran: " ДЛИHA ПOДПPOГPAMMЫ HELLO  00016"
+static SUBROUTINE_LENGTH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"ДЛИHA ПOДПPOГPAMMЫ +(\S+) +([0-7]+)").unwrap()
+});
+
+// БЕМШ: "MAIN     HAM=01000   ДЛИHA MOДЛ=00001  ДЛИHA ПPOГ=00010 BXOДH=00001"
+static MODULE_LENGTH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^ *(\S+) +HAM=([0-7]+) +ДЛИHA MOДЛ=([0-7]+)(?: +ДЛИHA ПPOГ=([0-7]+))?(?: +BXOДH=([0-7]+))?").unwrap()
+});
+
+// Object library: " ДЛИHA LIBRARY 0001 ЗOH."
+static LIBRARY_ZONES: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"ДЛИHA LIBRARY +(\d+) +ЗOH").unwrap()
+});
+
+// Executable: " ДЛИHA БИБЛИOTEKИ  002 17"
+pub(crate) static LIBRARY_LENGTH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^ ДЛИHA БИБЛИOTEKИ +(.*\S)").unwrap()
+});
+
+//
+// Size of one module, as reported by the compiler or loader.
+// All numbers are in words.
+//
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleSize {
+    pub name: String,
+
+    /// Length of the program
+    pub words: u32,
+
+    /// Load address, when reported
+    pub address: Option<u32>,
+
+    /// Length of the module record, when reported separately
+    pub module_words: Option<u32>,
+
+    /// Entry offset, when reported
+    pub entry: Option<u32>,
+}
+
+//
+// Everything the loader tells about sizes.
+//
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LinkSummary {
+    /// Modules, in order of the listing
+    pub modules: Vec<ModuleSize>,
+
+    /// Length of object library in zones, for -c
+    pub library_zones: Option<u32>,
+
+    /// Length of executable, as printed, like "002 17"
+    pub library_length: Option<String>,
+}
+
+impl LinkSummary {
+    //
+    // Sum of lengths of all modules, in words.
+    //
+    pub fn total_words(&self) -> u32 {
+        self.modules.iter().map(|module| module.words).sum()
+    }
+
+    //
+    // Length of the executable in words, for --max-size, from the loader's
+    // "ДЛИHA БИБЛИOTEKИ  002 17": octal pages of 1024 words, and octal blocks of 32 words.
+    // This is the length of the whole program, routines of libraries included:
+    // in the listings of examples/README.md, БЕМШ program of 8 words is "001 01",
+    // and the second number is below 40 octal in all of them ("01", "03", "17", "30").
+    //
+    pub fn library_words(&self) -> Option<u32> {
+        let length = self.library_length.as_ref()?;
+        let mut fields = length.split_whitespace();
+        let pages = octal(fields.next()?)?;
+        let blocks = octal(fields.next()?)?;
+        if blocks >= 0o40 || fields.next().is_some() {
+            return None;
+        }
+        Some(pages * 1024 + blocks * 32)
+    }
+
+    //
+    // Largest modules, up to given number, largest first.
+    //
+    pub fn largest_modules(&self, count: usize) -> Vec<ModuleSize> {
+        let mut modules = self.modules.clone();
+        modules.sort_by_key(|module| std::cmp::Reverse(module.words));
+        modules.truncate(count);
+        modules
+    }
+
+    //
+    // Add modules and lengths from another summary.
+    //
+    pub fn merge(&mut self, other: LinkSummary) {
+        self.modules.extend(other.modules);
+        self.library_zones = self.library_zones.or(other.library_zones);
+        self.library_length = self.library_length.take().or(other.library_length);
+    }
+}
+
+fn octal(text: &str) -> Option<u32> {
+    u32::from_str_radix(text, 8).ok()
+}
+
+//
+// Parse number of words for --max-size: decimal like 32768,
+// or octal like 0o100000 or 100000B, as in listings.
+//
+pub fn parse_words(text: &str) -> Result<u32, String> {
+    let parsed = if let Some(digits) = text.strip_prefix("0o") {
+        u32::from_str_radix(digits, 8)
+    } else if let Some(digits) = text.strip_suffix('B') {
+        u32::from_str_radix(digits, 8)
+    } else {
+        text.parse()
+    };
+    parsed.map_err(|_| format!("invalid number of words: {}", text))
+}
+
+//
+// Size of a module, when the line reports it.
+//
+pub fn parse_module_line(line: &str) -> Option<ModuleSize> {
+    if let Some(captures) = MODULE_LENGTH.captures(line) {
+        let module_words = octal(&captures[3]);
+        let program_words = captures.get(4).and_then(|m| octal(m.as_str()));
+        return Some(ModuleSize {
+            name: captures[1].to_string(),
+            words: program_words.or(module_words).unwrap_or(0),
+            address: octal(&captures[2]),
+            module_words: program_words.and(module_words),
+            entry: captures.get(5).and_then(|m| octal(m.as_str())),
+        });
+    }
+    let captures = SUBROUTINE_LENGTH.captures(line)
+                                    .or_else(|| ALGOL_LENGTH.captures(line))
+                                    .or_else(|| FTN_LENGTH.captures(line))?;
+    Some(ModuleSize {
+        name: captures[1].to_string(),
+        words: octal(&captures[2]).unwrap_or(0),
+        address: None,
+        module_words: None,
+        entry: None,
+    })
+}
+
+//
+// Find sizes in the listing text.
+//
+pub fn parse_loader_output(text: &str) -> LinkSummary {
+    let mut summary = LinkSummary::default();
+    for line in text.lines() {
+        if let Some(module) = parse_module_line(line) {
+            summary.modules.push(module);
+        } else if let Some(captures) = LIBRARY_ZONES.captures(line) {
+            summary.library_zones = captures[1].parse().ok();
+        } else if let Some(captures) = LIBRARY_LENGTH.captures(line) {
+            summary.library_length.get_or_insert(captures[1].to_string());
+        }
+    }
+    summary
+}
+
+//
+// Find sizes in the listing file.
+//
+pub fn parse_loader_output_file(listing_file: &str) -> Result<LinkSummary, BesmcError> {
+    let bytes = fs::read(listing_file)
+                   .map_err(|e| BesmcError::io("read", listing_file, e))?;
+    Ok(parse_loader_output(&String::from_utf8_lossy(&bytes)))
+}
+
+//
+// How to print the size report.
+//
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Table with octal and decimal word counts
+    #[default]
+    Text,
+
+    /// JSON object
+    Json,
+}
+
+//
+// Per-module table of sizes, with totals.
+//
+pub fn format_size_report(format: ReportFormat, summary: &LinkSummary) -> String {
+    if format == ReportFormat::Json {
+        let mut json = serde_json::to_value(summary).expect("Cannot serialize size report");
+        json["total_words"] = summary.total_words().into();
+        return format!("{:#}\n", json);
+    }
+    let width = summary.modules.iter()
+                       .map(|module| module.name.chars().count())
+                       .max()
+                       .unwrap_or(0)
+                       .max("Module".len());
+    let mut text = String::new();
+    let _ = writeln!(text, "{:<width$}  {:>8}  {:>8}", "Module", "Octal", "Decimal");
+    for module in &summary.modules {
+        let _ = writeln!(text, "{:<width$}  {:>8o}  {:>8}", module.name, module.words, module.words);
+    }
+    let total = summary.total_words();
+    let _ = writeln!(text, "{:<width$}  {:>8o}  {:>8}", "Total", total, total);
+    if let Some(zones) = summary.library_zones {
+        let _ = writeln!(text, "Object library: {} zones", zones);
+    }
+    if let Some(length) = &summary.library_length {
+        let _ = writeln!(text, "Library length: {}", length);
+    }
+    text
+}
@@ -1,77 +1,46 @@
 use clap::Parser;
-use std::panic;
-
-mod compiler;
-use compiler::compile_files;
-
-#[cfg(test)]
-mod test;
-
-// Data structure to hold all parsed compiler options and files
-#[derive(Debug, Parser, Default)]
-#[command(
-    about = "BESM-6 compiler frontend",
-    disable_help_flag = false,
-    arg_required_else_help = true
-)]
-struct CompilerOptions {
-    /// Output file name
-    #[arg(short = 'o', long = "output", value_name = "FILE")]
-    output_file: Option<String>,
-
-    /// Compile only to object files
-    #[arg(short = 'c', long = "compile")]
-    stop_at_object: bool,
-
-    /// Keep intermediate files
-    #[arg(short = 't', long = "save-temps")]
-    save_temps: bool,
-
-    // Input files
-    #[arg(
-        value_name = "FILES",
-        help = "Input sources and object files:\n\
-                 *.ftn     - Fortran-ГДP\n\
-                 *.fortran - Fortran Dubna\n\
-                 *.forex   - Forex\n\
-                 *.algol   - Algol-ГДP\n\
-                 *.pascal  - Pascal\n\
-                 *.pas     - Pascal-re\n\
-                 *.assem   - Assembler Madlen\n\
-                 *.madlen  - Assembler Madlen-3.5\n\
-                 *.bemsh   - Assembler БЕМШ\n\
-                 *.c       - C language\n\
-                 *.obj     - Object Library (*perso)\n\
-                 *.std     - Standard array (*punch)"
-    )]
-    files: Vec<String>,
-}
+use besmc::{Build, CompilerOptions, DiagnosticsFormat, format_diagnostics, format_size_report};
 
 fn main() {
-    // Set empty panic hook.
-    panic::set_hook(Box::new(|_| {}));
-
-    // Use catch_unwind to handle fatal errors.
-    let result = panic::catch_unwind(|| {
-
-        // Parse arguments using clap
-        let options = CompilerOptions::parse();
-
-        // Print the parsed options for debug
-        //println!("Options: {:#?}", options);
-
-        compile_files(&options)
-    });
+    // Parse arguments using clap.
+    // Option -### is a synonym for --dry-run, as in gcc.
+    let args = std::env::args().map(|arg| if arg == "-###" { "--dry-run".to_string() } else { arg });
+    let options = CompilerOptions::parse_from(args);
+
+    // Print the parsed options for debug
+    //println!("Options: {:#?}", options);
+
+    let format = options.diagnostics_format;
+    let size_report = options.size_report;
+    let result = Build::new(options).run();
+
+    // Show messages from the listing.
+    let diagnostics = match &result {
+        Ok(artifacts) => &artifacts.diagnostics[..],
+        Err(err) => err.diagnostics(),
+    };
+    let report = format_diagnostics(format, diagnostics);
+    if format == DiagnosticsFormat::Text {
+        eprint!("{}", report);
+    } else {
+        print!("{}", report);
+    }
 
-    if let Err(panic_err) = result {
-        // Extract and print the panic message
-        if let Some(msg) = panic_err.downcast_ref::<String>() {
-            eprintln!("{}", msg);
-        } else if let Some(msg) = panic_err.downcast_ref::<&str>() {
-            eprintln!("{}", msg);
-        } else {
-            eprintln!("Unknown error occurred");
+    match result {
+        Ok(artifacts) => {
+            for warning in &artifacts.warnings {
+                eprintln!("{}", warning);
+            }
+            if let Some(report_format) = size_report {
+                print!("{}", format_size_report(report_format, &artifacts.link));
+            }
+            if let Some(status) = artifacts.status {
+                std::process::exit(status); // Exit with status of the program
+            }
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(err.exit_code()); // Exit with error code
         }
-        std::process::exit(1); // Exit with error code
     }
 }
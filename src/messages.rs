@@ -0,0 +1,161 @@
+use std::fs;
+use std::sync::LazyLock;
+use clap::ValueEnum;
+use regex::Regex;
+
+use super::BesmcError;
+
+//
+// How to show messages of BESM-6 compilers and loader.
+//
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Messages {
+    /// As printed in the listing, with Latin letters in place of similar Cyrillic ones
+    Original,
+
+    /// In proper Cyrillic
+    #[default]
+    Ru,
+
+    /// Translated to English, when the message is known
+    En,
+}
+
+// Russian words in listings, which are spelled with Latin letters only.
+const LATIN_WORDS: &[&str] = &[
+    "OTCYTCTBYET",
+    "HET",
+    "HE",
+    "BCE",
+    "BCEX",
+    "TEKCT",
+];
+
+// Known messages in proper Cyrillic, with English translation.
+// Longer messages go first, as they may contain shorter ones.
+const TRANSLATIONS: &[(&str, &str)] = &[
+    (r"БЫЛИ ОШИБКИ ПРИ ВВОДЕ ИЛИ ТРАНСЛЯЦИИ",  "THERE WERE ERRORS IN INPUT OR TRANSLATION"),
+    (r"ОТСУТСТВУЕТ ИМЯ ПРОГРАММЫ",             "PROGRAM NAME IS MISSING"),
+    (r"ОТСУТСТВУЕТ ИМЯ ПОДПРОГРАММЫ",          "SUBROUTINE NAME IS MISSING"),
+    (r"ОТСУТСТВУЕТ ЗАГОЛОВОК ПОДПРОГРАММЫ",    "SUBROUTINE HEADER IS MISSING"),
+    (r"\bОТСУТСТВУЕТ\b",                        "MISSING"),
+    (r"ЗНАЧЕН\S* (.*\S) +НЕ ОПРЕДЕЛЕНО",       "VALUE $1 IS UNDEFINED"),
+    (r"ЗНАЧЕН\S* (.*\S) +НЕ ИСПОЛЬЗУЕТСЯ",     "VALUE $1 IS NOT USED"),
+    (r"НЕОПИСАННЫЙ ИДЕНТИФИКАТОР",             "UNDECLARED IDENTIFIER"),
+    (r"НЕДОПУСТИМЫЙ ОПЕРАТОР",                 "INVALID STATEMENT"),
+    (r"ДЛИННЫЙ АДРЕС В",                       "LONG ADDRESS IN"),
+    (r"НЕОП МЕТ",                              "UNDEFINED LABEL"),
+    (r"\bНЕТ\b",                              "NO"),
+    (r"ДЛИНА БИБЛИОТЕКИ",                      "LIBRARY LENGTH"),
+    (r"ЧИСЛО ОШИБ\. ОПЕРАТОРОВ",               "STATEMENTS WITH ERRORS"),
+    (r"ЧИСЛО ПЕРФ\.",                          "CARDS"),
+    (r"ОШИБКИ НА СТР\.",                       "ERRORS ON PAGE"),
+];
+
+static TRANSLATION_REGEXES: LazyLock<Vec<(Regex, &str)>> = LazyLock::new(|| {
+    TRANSLATIONS.iter()
+                .map(|(pattern, english)| (Regex::new(pattern).expect("Invalid regex pattern"), *english))
+                .collect()
+});
+
+//
+// Cyrillic letter, which looks like given Latin letter.
+//
+fn cyrillic_lookalike(c: char) -> Option<char> {
+    match c {
+        'A' => Some('А'), 'B' => Some('В'), 'C' => Some('С'), 'E' => Some('Е'),
+        'H' => Some('Н'), 'K' => Some('К'), 'M' => Some('М'), 'O' => Some('О'),
+        'P' => Some('Р'), 'T' => Some('Т'), 'X' => Some('Х'), 'Y' => Some('У'),
+        _ => None,
+    }
+}
+
+fn is_cyrillic(c: char) -> bool {
+    ('\u{0400}'..='\u{04FF}').contains(&c)
+}
+
+//
+// Whether a Latin-looking word is actually Russian.
+// It is, when it mixes Latin letters with Cyrillic ones, when it is
+// a known Russian word, or when it continues a Russian phrase.
+//
+fn is_russian(word: &str, after_russian: bool) -> bool {
+    let lookalikes_only = word.chars().all(|c| is_cyrillic(c) || cyrillic_lookalike(c).is_some());
+    if !lookalikes_only {
+        return false;
+    }
+    word.chars().any(is_cyrillic) || after_russian || LATIN_WORDS.contains(&word)
+}
+
+//
+// Convert text of the listing to proper Cyrillic:
+// Latin letters in Russian words are replaced by similar Cyrillic ones.
+// English words, like PROGRAM or ERROR, are left as is.
+//
+pub fn normalize(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+    let mut after_russian = false;
+    for c in text.chars().chain(std::iter::once('\n')) {
+        if c.is_alphabetic() {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            after_russian = is_russian(&word, after_russian);
+            if after_russian {
+                result.extend(word.chars().map(|c| cyrillic_lookalike(c).unwrap_or(c)));
+            } else {
+                result.push_str(&word);
+            }
+            word.clear();
+        }
+
+        // Phrase continues over spaces and some punctuation, but not over
+        // numbers, quotes or a period: names may follow abbreviations, like "ЗHAЧEH. A".
+        if !(c == ' ' || ",:;!-".contains(c)) {
+            after_russian = false;
+        }
+        result.push(c);
+    }
+    result.pop();
+    result
+}
+
+//
+// Translate known messages to English.
+// Expects text in proper Cyrillic, see normalize().
+//
+pub fn translate(text: &str) -> String {
+    let mut text = text.to_string();
+    for (regex, english) in TRANSLATION_REGEXES.iter() {
+        if regex.is_match(&text) {
+            text = regex.replace_all(&text, *english).into_owned();
+        }
+    }
+    text
+}
+
+//
+// Show text of the listing in the requested way.
+//
+pub fn render(text: &str, messages: Messages) -> String {
+    match messages {
+        Messages::Original => text.to_string(),
+        Messages::Ru       => normalize(text),
+        Messages::En       => translate(&normalize(text)),
+    }
+}
+
+//
+// Rewrite the listing file in the requested way.
+//
+pub fn render_listing_file(listing_file: &str, messages: Messages) -> Result<(), BesmcError> {
+    if messages == Messages::Original {
+        return Ok(());
+    }
+    let bytes = fs::read(listing_file)
+                   .map_err(|e| BesmcError::io("read", listing_file, e))?;
+    fs::write(listing_file, render(&String::from_utf8_lossy(&bytes), messages))
+        .map_err(|e| BesmcError::io("write", listing_file, e))
+}
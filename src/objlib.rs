@@ -0,0 +1,338 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use clap::Subcommand;
+
+use super::{CompilerOptions, BuildArtifacts, BesmcError};
+use super::compiler::{compile_job, has_extension};
+use super::linkmap::LIBRARY_LOADED;
+use super::disasm::module_layout;
+use super::plan::{Emit, shell_quote};
+use super::stdarray::StdArray;
+use super::workdir::WorkDir;
+
+//
+// What to do with an object library.
+//
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum LibAction {
+    /// Show modules of the library, with their lengths and entry points
+    List {
+        /// Object library (.obj)
+        library: String,
+    },
+
+    /// Compile sources and add their modules to the library, creating it when needed
+    Add {
+        /// Object library (.obj)
+        library: String,
+
+        /// Sources or object files to add
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+
+    /// Compile sources and put their modules in place of the old ones with the same names
+    Replace {
+        /// Object library (.obj)
+        library: String,
+
+        /// Sources or object files with new versions of modules
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+
+    /// Write one module of the library to MODULE.obj, or to the file given by -o
+    Extract {
+        /// Object library (.obj)
+        library: String,
+
+        /// Name of the module
+        module: String,
+    },
+}
+
+//
+// Module of an object library, as punched by Dubna.
+//
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryModule {
+    pub name: String,
+
+    /// Length of code and constants, or of the whole module when its layout is unknown
+    pub words: u32,
+
+    /// Entries, with their addresses in the module
+    pub entries: Vec<(String, usize)>,
+}
+
+//
+// Contents of an object library: modules of its standard array.
+//
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryContents {
+    pub library: String,
+
+    /// Date of the library, like "14/04/25 00.28.36", as reported by Dubna when loading it
+    pub created: Option<String>,
+
+    /// Modules, in order of the library
+    pub modules: Vec<LibraryModule>,
+}
+
+//
+// Collect contents of the library from its standard array,
+// and the listing of the job which punched it.
+//
+pub fn library_contents(library: &str, array: &StdArray, listing: &str) -> LibraryContents {
+    let created = listing.lines()
+                         .find_map(|line| LIBRARY_LOADED.captures(line))
+                         .map(|captures| captures[1].to_string());
+    LibraryContents { library: library.to_string(), created, modules: library_modules(array) }
+}
+
+//
+// Modules of a standard array, with lengths and entries from their headers.
+//
+pub(crate) fn library_modules(array: &StdArray) -> Vec<LibraryModule> {
+    array.modules.iter().map(|module| {
+        let words: Vec<u64> = module.words().collect();
+        match module_layout(&words) {
+            Some(layout) => LibraryModule {
+                name: module.name.clone(),
+                words: layout.program_words() as u32,
+                entries: layout.entries,
+            },
+            None => LibraryModule { name: module.name.clone(), words: words.len() as u32, entries: Vec::new() },
+        }
+    }).collect()
+}
+
+//
+// Show the contents as a table, with lengths in octal and decimal.
+//
+impl fmt::Display for LibraryContents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Library {}", self.library)?;
+        if let Some(created) = &self.created {
+            write!(f, ", created {}", created)?;
+        }
+        writeln!(f)?;
+        if self.modules.is_empty() {
+            return writeln!(f, "No modules in the library");
+        }
+        let width = self.modules.iter()
+                                .map(|module| module.name.chars().count())
+                                .max()
+                                .unwrap_or(0)
+                                .max("Module".len());
+        writeln!(f, "  {:<width$}  {:>7}  {:>7}  Entries", "Module", "Octal", "Decimal")?;
+        for module in &self.modules {
+            let entries: Vec<String> = module.entries.iter()
+                                                     .map(|(name, address)| format!("{} at {:05o}", name, address))
+                                                     .collect();
+            writeln!(f, "  {:<width$}  {:>7o}  {:>7}  {}", module.name, module.words, module.words, entries.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+//
+// Check that the file is an object library, and that it exists when needed.
+//
+fn check_library(library: &str, must_exist: bool) -> Result<(), BesmcError> {
+    if !has_extension(library, ".obj") {
+        return Err(BesmcError::UnknownExtension { file: library.to_string() });
+    }
+    if must_exist {
+        fs::metadata(library)
+           .map_err(|e| BesmcError::io("open", library, e))?;
+    }
+    Ok(())
+}
+
+//
+// Run the action on an object library.
+// Options given before the action, like -v or --dry-run, apply to Dubna jobs.
+//
+pub fn run_lib(action: &LibAction, options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    match action {
+        LibAction::List { library } => {
+            let (contents, artifacts) = list_library(library, options)?;
+            if !options.dry_run {
+                print!("{}", contents);
+            }
+            Ok(artifacts)
+        }
+        LibAction::Add { library, files } => {
+            check_library(library, false)?;
+            let mut inputs = Vec::new();
+            if Path::new(library).exists() {
+                inputs.push(library.clone());
+            }
+            inputs.extend(files.iter().cloned());
+            update_library(library, inputs, options)
+        }
+        LibAction::Replace { library, files } => {
+            check_library(library, !options.dry_run)?;
+            replace_modules(library, files, options)
+        }
+        LibAction::Extract { library, module } => {
+            check_library(library, !options.dry_run)?;
+            let output = match &options.output_file {
+                Some(output) => output.clone(),
+                None => format!("{}.obj", module.to_lowercase()),
+            };
+            extract_module(library, module, &output, options)
+        }
+    }
+}
+
+//
+// Write one module of the library to a separate object file:
+// punch the library to a standard array, keep the module,
+// and load it back into an object library.
+//
+fn extract_module(library: &str, module: &str, output: &str, options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    let workdir = if options.dry_run {
+        WorkDir::placeholder()
+    } else {
+        WorkDir::create(options.save_temps)?
+    };
+    let (array, _) = punch_library(library, &workdir, options)?;
+    let selected = workdir.file("module.std");
+    if !options.dry_run {
+        let Some(found) = array.module(module) else {
+            return Err(BesmcError::ModuleNotFound {
+                library: library.to_string(),
+                module: module.to_string(),
+                modules: array.modules.iter().map(|m| m.name.clone()).collect(),
+            });
+        };
+        StdArray { modules: vec![found.clone()] }.write(&selected)?;
+    }
+    let mut artifacts = run_convert(&selected, output, options)?;
+    if !options.dry_run {
+        artifacts.object = Some(output.to_string());
+    }
+    Ok(artifacts)
+}
+
+//
+// Put new versions of modules in place of the old ones with the same names:
+// punch the library and the inputs to standard arrays, drop the old modules,
+// and load the rest back into an object library.
+// New modules keep the place of the old ones; others are added at the end.
+//
+fn replace_modules(library: &str, files: &[String], options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    let workdir = if options.dry_run {
+        WorkDir::placeholder()
+    } else {
+        WorkDir::create(options.save_temps)?
+    };
+    let old_file = workdir.file("library.std");
+    run_convert(library, &old_file, options)?;
+    let new_file = workdir.file("modules.std");
+    let listing_file = workdir.file("modules.lst");
+    let compile_options = CompilerOptions {
+        files: files.to_vec(),
+        stop_at_object: true,
+        emit: Emit::Std,
+        ..options.clone()
+    };
+    compile_job(&compile_options, &new_file, &listing_file)?;
+
+    let merged_file = workdir.file("merged.std");
+    if !options.dry_run {
+        let mut merged = StdArray::load(&old_file)?;
+        merged.replace_modules(StdArray::load(&new_file)?);
+        merged.write(&merged_file)?;
+    }
+    update_library(library, vec![merged_file], options)
+}
+
+//
+// Punch the library by a Dubna job, and list the modules of the standard array.
+//
+pub fn list_library(library: &str, options: &CompilerOptions) -> Result<(LibraryContents, BuildArtifacts), BesmcError> {
+    check_library(library, !options.dry_run)?;
+    let workdir = if options.dry_run {
+        WorkDir::placeholder()
+    } else {
+        WorkDir::create(options.save_temps)?
+    };
+    let (array, artifacts) = punch_library(library, &workdir, options)?;
+    if options.dry_run {
+        return Ok((LibraryContents { library: library.to_string(), ..Default::default() }, artifacts));
+    }
+    let text = fs::read(&artifacts.listing)
+                  .map_err(|e| BesmcError::io("read", &artifacts.listing, e))?;
+    Ok((library_contents(library, &array, &String::from_utf8_lossy(&text)), artifacts))
+}
+
+//
+// Punch the library to a standard array in the work directory, and load it.
+// With --dry-run, the job is only shown, and the array is empty.
+//
+pub(crate) fn punch_library(library: &str, workdir: &WorkDir, options: &CompilerOptions) -> Result<(StdArray, BuildArtifacts), BesmcError> {
+    let name = Path::new(library).file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let punched = workdir.file(&format!("{}.std", name));
+    let artifacts = run_convert(library, &punched, options)?;
+    if options.dry_run {
+        return Ok((StdArray::default(), artifacts));
+    }
+    Ok((StdArray::load(&punched)?, artifacts))
+}
+
+//
+// Create a new version of the library from input files by one Dubna job:
+// modules are loaded from perso disks, so only the sources are compiled.
+// The library is replaced only when the job succeeds.
+// Modules are saved in order of the inputs.
+//
+fn update_library(library: &str, files: Vec<String>, options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    let new_library = format!("{}.new", library);
+    let listing_file = Path::new(library).with_extension("lst").to_string_lossy().into_owned();
+    let update_options = CompilerOptions {
+        files,
+        stop_at_object: true,
+        emit: Emit::Obj,
+        ..options.clone()
+    };
+    let mut artifacts = compile_job(&update_options, &new_library, &listing_file)?;
+    if options.dry_run {
+        println!("mv {} {}", shell_quote(&new_library), shell_quote(library));
+        return Ok(artifacts);
+    }
+    fs::rename(&new_library, library)
+       .map_err(|e| BesmcError::io("rename", &new_library, e))?;
+    artifacts.object = Some(library.to_string());
+    Ok(artifacts)
+}
+
+//
+// Convert an object library to a standard array, or back, by one Dubna job.
+// Direction is given by extensions of the files.
+// The listing is written next to the output.
+//
+pub fn run_convert(input: &str, output: &str, options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    let emit = if has_extension(input, ".obj") && has_extension(output, ".std") {
+        Emit::Std
+    } else if has_extension(input, ".std") && has_extension(output, ".obj") {
+        Emit::Obj
+    } else {
+        return Err(BesmcError::Unsupported {
+            what: format!("convert {} to {}", input, output),
+            reason: "one file must be an object library (.obj), and the other a standard array (.std)".to_string(),
+        });
+    };
+    let listing_file = Path::new(output).with_extension("lst").to_string_lossy().into_owned();
+    let convert_options = CompilerOptions {
+        files: vec![input.to_string()],
+        output_file: Some(output.to_string()),
+        stop_at_object: true,
+        emit,
+        ..options.clone()
+    };
+    compile_job(&convert_options, output, &listing_file)
+}
@@ -0,0 +1,324 @@
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::time::Instant;
+use clap::ValueEnum;
+
+use super::BesmcError;
+use super::listing::Unit;
+
+//
+// External command: a compiler pass, like pascompl or b6parse.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCommand {
+    pub program: String,
+    pub args: Vec<String>,
+
+    /// Source file this command works on, for error messages
+    pub source: String,
+}
+
+impl ToolCommand {
+    pub fn new(program: &str, args: &[&str], source: &str) -> Self {
+        ToolCommand {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            source: source.to_string(),
+        }
+    }
+
+    //
+    // Run the command in the current directory and fail if it fails.
+    // When verbose, show the command and its result.
+    //
+    pub fn run(&self, verbose: bool) -> Result<(), BesmcError> {
+        let dir = std::env::current_dir()
+                           .map_err(|e| BesmcError::io("get current directory", ".", e))?;
+        self.execute(Command::new(&self.program), &dir, None, verbose)
+    }
+
+    //
+    // Run the command in given directory, with output to a file.
+    //
+    pub fn run_in(&self, dir: &Path, output: fs::File, output_name: &str, verbose: bool) -> Result<(), BesmcError> {
+        let mut command = Command::new(&self.program);
+        command.current_dir(dir)
+               .stdout(Stdio::from(output));
+        self.execute(command, dir, Some(output_name), verbose)
+    }
+
+    //
+    // Run the command in the current directory, with input from a file, if given,
+    // and return its status, whatever it is.
+    //
+    pub fn run_with_input(&self, input: Option<&str>, verbose: bool) -> Result<ExitStatus, BesmcError> {
+        let dir = std::env::current_dir()
+                           .map_err(|e| BesmcError::io("get current directory", ".", e))?;
+        let mut command = Command::new(&self.program);
+        self.redirect_input(&mut command, input)?;
+        self.launch(command, self.command_line(&dir, input, None), verbose, Command::status, |status| *status)
+    }
+
+    //
+    // Run the command in the current directory, with input from a file, if given,
+    // and collect its output. Error messages go to stderr as usual.
+    //
+    pub fn output_with_input(&self, input: Option<&str>, verbose: bool) -> Result<Output, BesmcError> {
+        let dir = std::env::current_dir()
+                           .map_err(|e| BesmcError::io("get current directory", ".", e))?;
+        let mut command = Command::new(&self.program);
+        command.stdin(Stdio::null())
+               .stderr(Stdio::inherit());
+        self.redirect_input(&mut command, input)?;
+        self.launch(command, self.command_line(&dir, input, None), verbose, Command::output, |output| output.status)
+    }
+
+    //
+    // Command line as a shell would run it, for verbose and dry run output:
+    // "cd DIR && PROGRAM ARGS < INPUT > OUTPUT".
+    //
+    pub fn command_line(&self, dir: &Path, input: Option<&str>, output_name: Option<&str>) -> String {
+        let mut line = format!("cd {} && {}", shell_quote(&dir.to_string_lossy()), self);
+        if let Some(input) = input {
+            line += &format!(" < {}", shell_quote(input));
+        }
+        if let Some(output_name) = output_name {
+            line += &format!(" > {}", shell_quote(output_name));
+        }
+        line
+    }
+
+    fn redirect_input(&self, command: &mut Command, input: Option<&str>) -> Result<(), BesmcError> {
+        if let Some(input) = input {
+            let file = fs::File::open(input)
+                                .map_err(|e| BesmcError::io("open", input, e))?;
+            command.stdin(Stdio::from(file));
+        }
+        Ok(())
+    }
+
+    fn execute(&self, command: Command, dir: &Path, output_name: Option<&str>, verbose: bool) -> Result<(), BesmcError> {
+        let status = self.launch(command, self.command_line(dir, None, output_name), verbose, Command::status, |status| *status)?;
+        if !status.success() {
+            return Err(BesmcError::ToolFailed { tool: self.program.clone(), file: Some(self.source.clone()), status });
+        }
+        Ok(())
+    }
+
+    //
+    // Start the command by given method, like Command::status, and wait for it.
+    // When verbose, show the command line and its result.
+    //
+    fn launch<R>(&self, mut command: Command, line: String, verbose: bool,
+                 method: impl FnOnce(&mut Command) -> std::io::Result<R>,
+                 status: impl FnOnce(&R) -> ExitStatus) -> Result<R, BesmcError> {
+        if verbose {
+            eprintln!("{}", line);
+        }
+        let start = Instant::now();
+        let result = method(command.args(&self.args))
+                         .map_err(|e| {
+                             if verbose {
+                                 eprintln!("    {}: cannot execute: {}", self.program, e);
+                             }
+                             BesmcError::spawn(&self.program, e)
+                         })?;
+        if verbose {
+            eprintln!("    {}: {}, {:.3} sec", self.program, status(&result), start.elapsed().as_secs_f64());
+        }
+        Ok(result)
+    }
+}
+
+//
+// Quote a word for shell, when needed.
+//
+pub fn shell_quote(word: &str) -> String {
+    let is_plain = |c: char| c.is_alphanumeric() || "-_./=:,+%$".contains(c);
+    if !word.is_empty() && word.chars().all(is_plain) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+impl fmt::Display for ToolCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", shell_quote(&self.program))?;
+        for arg in &self.args {
+            write!(f, " {}", shell_quote(arg))?;
+        }
+        Ok(())
+    }
+}
+
+//
+// Piece of Dubna script: either literal text,
+// or contents of a file, which may not exist until the passes are run.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptPart {
+    Text(String),
+    Include(String),
+}
+
+//
+// Format of the output with -c.
+//
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// Object library, saved by *call to perso
+    #[default]
+    Obj,
+
+    /// Standard array, punched by *punch
+    Std,
+}
+
+impl Emit {
+    //
+    // Extension of the output file.
+    //
+    pub fn extension(self) -> &'static str {
+        match self {
+            Emit::Obj => "obj",
+            Emit::Std => "std",
+        }
+    }
+}
+
+//
+// Library searched by the loader, in order of the script.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Library {
+    /// Directive of Dubna script, like "*perso:40" or "*library:22"
+    pub directive: String,
+
+    /// Where the library comes from: input file or system library
+    pub origin: String,
+}
+
+//
+// Everything one Dubna job is going to do, decided before running anything.
+//
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+    /// Work directory, where Dubna runs
+    pub workdir: String,
+
+    /// Compiler passes to run before Dubna, in order
+    pub commands: Vec<ToolCommand>,
+
+    /// Files to copy into work directory: (from, to)
+    pub copies: Vec<(String, String)>,
+
+    /// Symbolic links to create in work directory: (target, link)
+    pub symlinks: Vec<(String, String)>,
+
+    /// Name of Dubna script, relative to work directory
+    pub script_name: String,
+
+    /// Contents of Dubna script
+    pub script: Vec<ScriptPart>,
+
+    /// Source files compiled by the script, in order
+    pub units: Vec<Unit>,
+
+    /// Libraries searched by the loader, in order
+    pub libraries: Vec<Library>,
+
+    /// Listing file, where Dubna output goes
+    pub listing: String,
+
+    /// Output file: executable or object library
+    pub output: String,
+
+    /// Whether output is executable
+    pub executable: bool,
+
+    /// Whether output is a standard array, punched to the listing
+    pub punched: bool,
+
+    /// Whether no input defines an entry, and program is assumed
+    pub entry_assumed: bool,
+}
+
+impl Plan {
+    //
+    // Text of Dubna script.
+    // Files which are not created yet are shown by name.
+    //
+    pub fn script_text(&self) -> String {
+        let mut text = String::new();
+        for part in &self.script {
+            match part {
+                ScriptPart::Text(line) => text.push_str(line),
+                ScriptPart::Include(file) => match fs::read_to_string(file) {
+                    Ok(contents) => text.push_str(&contents),
+                    Err(_) => text.push_str(&format!("<contents of {}>\n", file)),
+                },
+            }
+        }
+        text
+    }
+
+    //
+    // Create Dubna script in work directory.
+    //
+    pub fn write_script(&self) -> Result<(), BesmcError> {
+        let script_file = format!("{}/{}", self.workdir, self.script_name);
+        let mut script = fs::File::create(&script_file)
+                                  .map_err(|e| BesmcError::io("create", &script_file, e))?;
+        for part in &self.script {
+            match part {
+                ScriptPart::Text(line) => script.write_all(line.as_bytes())
+                                                .map_err(|e| BesmcError::io("write", &script_file, e))?,
+                ScriptPart::Include(file) => {
+                    let contents = fs::read(file)
+                                      .map_err(|e| BesmcError::io("open file", file, e))?;
+                    script.write_all(&contents)
+                          .map_err(|e| BesmcError::io("write", &script_file, e))?;
+                }
+            }
+        }
+
+        // Ensure the file is written to disk
+        script.flush()
+              .map_err(|e| BesmcError::io("flush", &script_file, e))
+    }
+}
+
+//
+// Show the plan as a shell script, which does the same job.
+//
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let workdir = shell_quote(&self.workdir);
+        for command in &self.commands {
+            writeln!(f, "{}", command)?;
+        }
+        for (from, to) in &self.copies {
+            writeln!(f, "cp {} {}", shell_quote(from), shell_quote(to))?;
+        }
+        for (target, link) in &self.symlinks {
+            writeln!(f, "ln -s {} {}", shell_quote(target), shell_quote(link))?;
+        }
+        writeln!(f, "cat > {}/{} << 'EOF'", workdir, shell_quote(&self.script_name))?;
+        write!(f, "{}", self.script_text())?;
+        writeln!(f, "EOF")?;
+        writeln!(f, "(cd {} && dubna {}) > {}", workdir, shell_quote(&self.script_name), shell_quote(&self.listing))?;
+        let output = shell_quote(&self.output);
+        if self.punched {
+            writeln!(f, "grep '^`' {} > {}", shell_quote(&self.listing), output)
+        } else if self.executable {
+            writeln!(f, "(echo '#!/usr/bin/env dubna' && cat {}/output.bin) > {}", workdir, output)?;
+            writeln!(f, "chmod +x {}", output)
+        } else {
+            writeln!(f, "cp {}/output.bin {}", workdir, output)
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+
+use super::{CompilerOptions, BuildArtifacts, BesmcError};
+use super::compiler::compile_files;
+
+// Manifest looked for in the current directory.
+pub const MANIFEST: &str = "besmc.toml";
+
+//
+// What a target of the project produces.
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetKind {
+    /// Executable (.exe), linked from sources and libraries of dependencies
+    Exe,
+
+    /// Object library (.obj), compiled with -c
+    Lib,
+}
+
+//
+// Target as written in the TOML file.
+//
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    /// Unique name
+    pub name: String,
+
+    pub kind: TargetKind,
+
+    /// Sources in any supported language, and object files
+    pub sources: Vec<String>,
+
+    /// Output file (default: NAME.exe or NAME.obj)
+    #[serde(default)]
+    pub output: Option<String>,
+
+    /// Library targets to build first; executables are linked with them
+    #[serde(default)]
+    pub deps: Vec<String>,
+
+    /// Entry of the executable, as -e (default: found in the sources)
+    #[serde(default)]
+    pub entry: Option<String>,
+
+    /// Libraries to link with, as -l
+    #[serde(default)]
+    pub libraries: Vec<String>,
+
+    /// Directories to search for libraries, as -L
+    #[serde(default)]
+    pub library_dirs: Vec<String>,
+}
+
+impl Target {
+    //
+    // Output file, with extension of its kind: NAME.exe or NAME.obj by default.
+    //
+    pub fn output_file(&self) -> String {
+        let extension = match self.kind {
+            TargetKind::Exe => "exe",
+            TargetKind::Lib => "obj",
+        };
+        let output = self.output.as_deref().unwrap_or(&self.name);
+        Path::new(output).with_extension(extension).to_string_lossy().into_owned()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    #[serde(default)]
+    target: Vec<Target>,
+}
+
+//
+// Project: targets of the manifest, with paths relative to its directory.
+//
+#[derive(Debug, Clone, Default)]
+pub struct Project {
+    pub targets: Vec<Target>,
+}
+
+impl Project {
+    //
+    // Read the manifest file.
+    // Names of files in it are relative to the directory of the manifest.
+    //
+    pub fn load(file: &str) -> Result<Project, BesmcError> {
+        let text = fs::read_to_string(file)
+                      .map_err(|e| BesmcError::io("read", file, e))?;
+        let dir = Path::new(file).parent().unwrap_or(Path::new(""));
+        let relative = |name: &String| dir.join(name).to_string_lossy().into_owned();
+        let mut project = Project::parse(&text, file)?;
+        for target in &mut project.targets {
+            target.sources = target.sources.iter().map(relative).collect();
+            target.output = target.output.as_ref().map(relative);
+            target.library_dirs = target.library_dirs.iter().map(relative).collect();
+            if target.output.is_none() {
+                target.output = Some(relative(&target.name));
+            }
+        }
+        Ok(project)
+    }
+
+    //
+    // Parse TOML text of the manifest, and check the targets:
+    // names are unique, sources are given, dependencies are known libraries.
+    //
+    pub fn parse(text: &str, file: &str) -> Result<Project, BesmcError> {
+        let bad = |message: String| BesmcError::BadManifest { file: file.to_string(), message };
+        let parsed: ManifestFile = toml::from_str(text).map_err(|e| bad(e.to_string()))?;
+        if parsed.target.is_empty() {
+            return Err(bad("no targets".to_string()));
+        }
+        let project = Project { targets: parsed.target };
+        for (index, target) in project.targets.iter().enumerate() {
+            if project.targets[..index].iter().any(|t| t.name == target.name) {
+                return Err(bad(format!("target {}: defined twice", target.name)));
+            }
+            if target.sources.is_empty() {
+                return Err(bad(format!("target {}: no sources", target.name)));
+            }
+            for dep in &target.deps {
+                match project.target(dep) {
+                    None => return Err(bad(format!("target {}: unknown dependency {}", target.name, dep))),
+                    Some(t) if t.kind != TargetKind::Lib =>
+                        return Err(bad(format!("target {}: dependency {} is not a library", target.name, dep))),
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(project)
+    }
+
+    //
+    // Target by name.
+    //
+    pub fn target(&self, name: &str) -> Option<&Target> {
+        self.targets.iter().find(|t| t.name == name)
+    }
+
+    //
+    // Targets to build, dependencies first: the given one, or all of them.
+    // Each target is built once. Cycles of dependencies are an error.
+    //
+    pub fn build_order(&self, name: Option<&str>, file: &str) -> Result<Vec<&Target>, BesmcError> {
+        let roots: Vec<&Target> = match name {
+            Some(name) => vec![self.target(name).ok_or_else(|| BesmcError::BadManifest {
+                file: file.to_string(),
+                message: format!("no target {}", name),
+            })?],
+            None => self.targets.iter().collect(),
+        };
+        let mut order = Vec::new();
+        let mut visiting = Vec::new();
+        for target in roots {
+            self.visit(target, &mut visiting, &mut order, file)?;
+        }
+        Ok(order)
+    }
+
+    fn visit<'a>(&'a self, target: &'a Target, visiting: &mut Vec<&'a str>, order: &mut Vec<&'a Target>, file: &str) -> Result<(), BesmcError> {
+        if order.iter().any(|t| t.name == target.name) {
+            return Ok(());
+        }
+        if visiting.contains(&target.name.as_str()) {
+            visiting.push(&target.name);
+            return Err(BesmcError::BadManifest {
+                file: file.to_string(),
+                message: format!("dependency cycle: {}", visiting.join(" -> ")),
+            });
+        }
+        visiting.push(&target.name);
+        for dep in &target.deps {
+            // Dependencies are checked by parse().
+            if let Some(dep) = self.target(dep) {
+                self.visit(dep, visiting, order, file)?;
+            }
+        }
+        visiting.pop();
+        order.push(target);
+        Ok(())
+    }
+
+    //
+    // Libraries an executable is linked with: output of every library
+    // it depends on, directly or not, in build order.
+    //
+    fn linked_objects(&self, target: &Target, file: &str) -> Result<Vec<String>, BesmcError> {
+        let order = self.build_order(Some(&target.name), file)?;
+        Ok(order.iter()
+                .filter(|t| t.name != target.name)
+                .map(|t| t.output_file())
+                .collect())
+    }
+
+    //
+    // Options to build one target by compile_files.
+    // Options of the command line, like -v or -j, apply to every target.
+    //
+    pub fn target_options(&self, target: &Target, options: &CompilerOptions, file: &str) -> Result<CompilerOptions, BesmcError> {
+        let mut files = target.sources.clone();
+        if target.kind == TargetKind::Exe {
+            files.extend(self.linked_objects(target, file)?);
+        }
+        let mut libraries = options.libraries.clone();
+        libraries.extend(target.libraries.iter().cloned());
+        let mut library_dirs = target.library_dirs.clone();
+        library_dirs.extend(options.library_dirs.iter().cloned());
+        Ok(CompilerOptions {
+            files,
+            output_file: Some(target.output_file()),
+            stop_at_object: target.kind == TargetKind::Lib,
+            entry: target.entry.clone().or(options.entry.clone()),
+            libraries,
+            library_dirs,
+            split_objects: false,
+            map: None,
+            command: None,
+            ..options.clone()
+        })
+    }
+}
+
+
+//
+// Build a target of the manifest, or all of them, with dependencies first.
+// Stop at the first target which fails.
+// Return artifacts of the last target built.
+//
+pub fn build_project(manifest: &str, target: Option<&str>, options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    let project = Project::load(manifest)?;
+    let mut artifacts = BuildArtifacts::default();
+    for target in project.build_order(target, manifest)? {
+        if options.verbose || options.dry_run {
+            eprintln!("Building target {}", target.name);
+        }
+        artifacts = compile_files(&project.target_options(target, options, manifest)?)?;
+    }
+    Ok(artifacts)
+}
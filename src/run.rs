@@ -0,0 +1,83 @@
+use std::fs;
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+
+use super::{CompilerOptions, BuildArtifacts, BesmcError};
+use super::compiler::compile_files;
+use super::plan::ToolCommand;
+use super::workdir::WorkDir;
+
+//
+// Exit status of a program, as a shell shows it: 128 plus signal number when killed.
+//
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    status.code().unwrap_or_else(|| 128 + status.signal().unwrap_or(0))
+}
+
+//
+// Build the executable and run it, the same way as running the file directly:
+// by its shebang line, through dubna. Program output goes to stdout.
+// The executable is built in a work directory and removed afterwards,
+// unless asked to keep it; then it is named as by a usual build.
+// When the build fails, its listing is copied next to the first input.
+//
+pub fn run_program(files: &[String], input: Option<&str>, keep: bool, options: &CompilerOptions) -> Result<BuildArtifacts, BesmcError> {
+    let first_file = files.first()
+                          .ok_or(BesmcError::NoInput)?;
+    let workdir = if keep || options.dry_run {
+        WorkDir::placeholder()
+    } else {
+        WorkDir::create(options.save_temps)?
+    };
+    let executable = if keep {
+        Path::new(options.output_file.as_ref().unwrap_or(first_file)).with_extension("exe").to_string_lossy().into_owned()
+    } else {
+        let name = Path::new(first_file).with_extension("exe");
+        workdir.file(&name.file_name().unwrap_or_default().to_string_lossy())
+    };
+    let build_options = CompilerOptions {
+        files: files.to_vec(),
+        output_file: Some(executable.clone()),
+        stop_at_object: false,
+        command: None,
+        ..options.clone()
+    };
+    let mut artifacts = match compile_files(&build_options) {
+        Ok(artifacts) => artifacts,
+        Err(BesmcError::CompilationFailed { listing, diagnostics }) if !keep => {
+            let kept = Path::new(first_file).with_extension("lst").to_string_lossy().into_owned();
+            fs::copy(&listing, &kept)
+               .map_err(|e| BesmcError::io("copy", &listing, e))?;
+            return Err(BesmcError::CompilationFailed { listing: kept, diagnostics });
+        }
+        Err(err) => return Err(err),
+    };
+
+    let command = program_command(&executable, first_file, &workdir);
+    if options.dry_run {
+        let dir = std::env::current_dir()
+                           .map_err(|e| BesmcError::io("get current directory", ".", e))?;
+        println!("{}", command.command_line(&dir, input, None));
+        return Ok(artifacts);
+    }
+    let status = command.run_with_input(input, options.verbose)?;
+    artifacts.status = Some(exit_code(status));
+    if !keep {
+        artifacts.executable = None;
+    }
+    Ok(artifacts)
+}
+
+//
+// Command which runs the executable by absolute name, so that it is not searched in $PATH.
+// An executable in the placeholder work directory of a dry run is named as is,
+// for the shell to expand $WORKDIR.
+//
+pub(crate) fn program_command(executable: &str, source: &str, workdir: &WorkDir) -> ToolCommand {
+    let program = if workdir.is_placeholder() && Path::new(executable).starts_with(workdir.path()) {
+        executable.to_string()
+    } else {
+        std::path::absolute(executable).map_or(executable.to_string(), |p| p.to_string_lossy().into_owned())
+    };
+    ToolCommand::new(&program, &[], source)
+}
@@ -0,0 +1,185 @@
+use std::fmt;
+use std::fs;
+use std::sync::LazyLock;
+use regex::Regex;
+
+use super::BesmcError;
+
+// Record header: "`77761 PROGRAM  1", with code, module name and record number.
+static RECORD_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^`([0-7]{5}) (\S+) +(\d+)$").unwrap()
+});
+
+// Word of 48 bits: "`5541515600000000".
+static WORD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^`[0-7]{16}$").unwrap()
+});
+
+//
+// Record of a standard array: a card image with a header and words.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StdRecord {
+    /// Code from the header, like 0o77761
+    pub code: u32,
+
+    /// Number of the record in the module, starting from 1
+    pub number: u32,
+
+    /// Words of 48 bits
+    pub words: Vec<u64>,
+}
+
+//
+// Module of a standard array: records with the same name, numbered in order.
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StdModule {
+    /// Name from the record headers, like PROGRAM
+    pub name: String,
+
+    pub records: Vec<StdRecord>,
+}
+
+impl StdModule {
+    //
+    // Words of all records, in order.
+    //
+    pub fn words(&self) -> impl Iterator<Item = u64> + '_ {
+        self.records.iter().flat_map(|record| record.words.iter().copied())
+    }
+}
+
+//
+// Standard array, as punched by *punch or by pascompl:
+// octal card images, one record header or one word per line.
+// The format has no checksums, so only the structure is checked.
+//
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StdArray {
+    pub modules: Vec<StdModule>,
+}
+
+impl StdArray {
+    //
+    // Parse and check the text of a standard array.
+    // On error, return line number (starting from 1) and what is wrong.
+    //
+    pub fn parse(text: &str) -> Result<StdArray, (usize, String)> {
+        let mut array = StdArray::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let line_number = index + 1;
+            if let Some(captures) = RECORD_HEADER.captures(line) {
+                let code = u32::from_str_radix(&captures[1], 8).unwrap_or(0);
+                let name = &captures[2];
+                let number: u32 = captures[3].parse()
+                                             .map_err(|_| (line_number, format!("bad record number {}", &captures[3])))?;
+
+                // Record 1 starts a new module, others continue the last one.
+                let module = match array.modules.last_mut() {
+                    Some(module) if number != 1 => module,
+                    _ => {
+                        array.modules.push(StdModule { name: name.to_string(), records: Vec::new() });
+                        array.modules.last_mut().unwrap()
+                    }
+                };
+                if module.name != name {
+                    return Err((line_number, format!("record {} of module {} is named {}", number, module.name, name)));
+                }
+                let expected = module.records.len() as u32 + 1;
+                if number != expected {
+                    return Err((line_number, format!("record {} of module {} is out of order, expected record {}",
+                                                     number, name, expected)));
+                }
+                module.records.push(StdRecord { code, number, words: Vec::new() });
+            } else if WORD.is_match(line) {
+                let word = u64::from_str_radix(&line[1..], 8).unwrap_or(0);
+                let record = array.modules.last_mut()
+                                          .and_then(|module| module.records.last_mut())
+                                          .ok_or((line_number, "word before the first record header".to_string()))?;
+                record.words.push(word);
+            } else if line.starts_with('`') {
+                return Err((line_number, format!("bad word {}: must be 16 octal digits", line)));
+            } else {
+                return Err((line_number, format!("bad line {:?}: must start with a backquote", line)));
+            }
+        }
+        if array.modules.is_empty() {
+            return Err((1, "no records".to_string()));
+        }
+        Ok(array)
+    }
+
+    //
+    // Collect the standard array punched by Dubna: the card images,
+    // which it prints to the listing, one per line, starting with a backquote.
+    // Line numbers in errors are those of the listing.
+    //
+    pub fn from_listing(text: &str) -> Result<StdArray, (usize, String)> {
+        let cards: Vec<(usize, &str)> = text.lines()
+                                            .enumerate()
+                                            .filter(|(_, line)| line.starts_with('`'))
+                                            .collect();
+        if cards.is_empty() {
+            return Err((1, "no card images punched".to_string()));
+        }
+        let punched: String = cards.iter().map(|(_, line)| format!("{}\n", line)).collect();
+        StdArray::parse(&punched).map_err(|(line, message)| (cards[line - 1].0 + 1, message))
+    }
+
+    //
+    // Read and check a standard array file.
+    //
+    pub fn load(file: &str) -> Result<StdArray, BesmcError> {
+        let bytes = fs::read(file)
+                       .map_err(|e| BesmcError::io("read", file, e))?;
+        StdArray::parse(&String::from_utf8_lossy(&bytes))
+                 .map_err(|(line, message)| BesmcError::BadStdArray { file: file.to_string(), line, message })
+    }
+
+    //
+    // Module with the given name, in any case of letters.
+    //
+    pub fn module(&self, name: &str) -> Option<&StdModule> {
+        self.modules.iter().find(|module| module.name.eq_ignore_ascii_case(name))
+    }
+
+    //
+    // Put new modules in place of the old ones with the same names,
+    // so that no old copy is left. Other new modules are added at the end.
+    //
+    pub fn replace_modules(&mut self, new: StdArray) {
+        for module in new.modules {
+            match self.modules.iter_mut().find(|old| old.name == module.name) {
+                Some(old) => *old = module,
+                None => self.modules.push(module),
+            }
+        }
+    }
+
+    //
+    // Write the standard array to a file.
+    //
+    pub fn write(&self, file: &str) -> Result<(), BesmcError> {
+        fs::write(file, self.to_string())
+            .map_err(|e| BesmcError::io("write", file, e))
+    }
+}
+
+//
+// Show as text, in the same layout as parsed.
+//
+impl fmt::Display for StdArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for module in &self.modules {
+            for record in &module.records {
+                writeln!(f, "`{:05o} {:<8} {}", record.code, module.name, record.number)?;
+                for word in &record.words {
+                    writeln!(f, "`{:016o}", word)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
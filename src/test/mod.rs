@@ -1,6 +1,8 @@
 use super::*;
 use std::io::BufRead;
 
+// Flags of options are compared to true and false, as the first tests did.
+#[allow(clippy::bool_assert_comparison)]
 mod test_options;
 mod test_exe;
 mod test_obj;
@@ -8,6 +10,19 @@ mod test_negative;
 mod test_obj_negative;
 mod test_pascal_to_fortran;
 mod test_stdarray;
+mod test_dry_run;
+mod test_listing;
+mod test_diagnostics;
+mod test_messages;
+mod test_catalog;
+mod test_loader;
+mod test_linkmap;
+mod test_lib;
+mod test_disasm;
+mod test_entry;
+mod test_run;
+mod test_golden;
+mod test_project;
 
 // Helper to parse args and return options, or panic message
 fn parse_and_process(args: Vec<&str>) -> CompilerOptions {
@@ -36,3 +51,29 @@ fn find_line_starting_with(filename: &str, prefix: &str) -> String {
     // No matching line found
     String::new() // Return empty string
 }
+
+//
+// Listing excerpt of a real Dubna job, quoted in examples/README.md
+// in the section of the given example. Notes after an arrow are dropped.
+//
+fn listing_excerpt(example: &str) -> String {
+    let readme = std::fs::read_to_string("examples/README.md").unwrap();
+    let section = &readme[readme.find(&format!("— `{}`", example)).unwrap()..];
+    let block = &section[section.find("**Compiler listing excerpt**").unwrap()..];
+    let block = &block[block.find("```text\n").unwrap() + 8..];
+    let block = &block[..block.find("```").unwrap()];
+    block.lines()
+         .map(|line| format!("{}\n", line.split(" ←").next().unwrap().trim_end()))
+         .collect()
+}
+
+//
+// Writes a source file of the test, and makes the compiler unit of it.
+//
+fn unit(file: &str, contents: &str) -> Unit {
+    std::fs::write(file, contents).expect("Cannot write file");
+    Unit {
+        file: file.to_string(),
+        language: Language::from_file(file).unwrap(),
+    }
+}
@@ -0,0 +1,119 @@
+use crate::test::*;
+
+#[test]
+fn test_catalog_builtin() {
+    let catalog = Catalog::builtin();
+    let ids: Vec<&str> = catalog.patterns().iter().map(|p| p.id.as_str()).collect();
+    assert!(ids.contains(&"monitor-errors"));
+    assert!(ids.contains(&"loader-no-program"));
+
+    let pattern = catalog.classify(" ****** HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR", Some(Language::Madlen)).unwrap();
+    assert_eq!(pattern.id, "madlen-undeclared");
+    assert_eq!(pattern.severity, Severity::Error);
+    assert_eq!(pattern.description, "Undeclared identifier");
+}
+
+#[test]
+fn test_catalog_per_language() {
+    // Message of Fortran-ГДP means nothing in Forex listing.
+    let catalog = Catalog::builtin();
+    assert!(catalog.classify("ERROR 138          UNDEFINED IDENTIFIER>B<", Some(Language::Ftn)).is_some());
+    assert!(catalog.classify("ERROR 138          UNDEFINED IDENTIFIER>B<", Some(Language::Forex)).is_none());
+
+    // Messages of the loader and of the monitor are found anywhere.
+    assert!(catalog.classify(" OTCYTCTBYET  PROGRAM", Some(Language::Forex)).unwrap().loader);
+    assert!(catalog.classify(" БЫЛИ OШИБKИ ПPИ BBOДE ИЛИ TPAHCЛЯЦИИ !!!", None).is_some());
+}
+
+//
+// When no banner of a compiler is found, messages of any compiler are recognized.
+//
+#[test]
+fn test_catalog_without_unit() {
+    let catalog = Catalog::builtin();
+    let pattern = catalog.classify("ERROR 138          UNDEFINED IDENTIFIER>B<", None).unwrap();
+    assert!(pattern.languages.contains(&Language::Ftn));
+
+    let pattern = catalog.classify(" ****** HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR", None).unwrap();
+    assert_eq!(pattern.id, "madlen-undeclared");
+
+    let diagnostics = scan_listing("ERROR 138          UNDEFINED IDENTIFIER>B<\n", &[], "hello.lst");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
+
+#[test]
+fn test_catalog_extend_and_override() {
+    let mut catalog = Catalog::builtin().clone();
+    catalog.extend(r#"
+[[pattern]]
+id = "b-error"
+languages = ["b"]
+regex = '^B ERROR'
+description = "Error in B program"
+
+[[pattern]]
+id = "forex-unused"
+languages = ["forex"]
+severity = "note"
+regex = 'ЗHAЧEH\. .* HE ИCПOЛЬЗYETCЯ'
+
+[[pattern]]
+id = "loader-missing"
+enabled = false
+"#, "test.toml").unwrap();
+
+    let pattern = catalog.classify("B ERROR 5", Some(Language::B)).unwrap();
+    assert_eq!(pattern.id, "b-error");
+    assert_eq!(pattern.severity, Severity::Error);
+
+    let pattern = catalog.classify(" ЗHAЧEH. A       HE ИCПOЛЬЗYETCЯ", Some(Language::Forex)).unwrap();
+    assert_eq!(pattern.severity, Severity::Note);
+
+    assert!(catalog.patterns().iter().all(|p| p.id != "loader-missing"));
+}
+
+#[test]
+fn test_catalog_bad_file() {
+    let mut catalog = Catalog::default();
+    let err = catalog.extend("[[pattern]]\nid = \"x\"\nlanguages = [\"cobol\"]\nregex = 'X'\n", "bad.toml").unwrap_err();
+    assert_eq!(err.exit_code(), 14);
+    assert!(err.to_string().contains("unknown language cobol"));
+
+    let err = catalog.extend("[[pattern]]\nid = \"x\"\nregex = '('\n", "bad.toml").unwrap_err();
+    assert!(err.to_string().starts_with("Bad catalog of messages bad.toml: pattern x:"));
+
+    let err = catalog.extend("[[pattern]]\nid = \"x\"\nregexp = 'X'\n", "bad.toml").unwrap_err();
+    assert_eq!(err.exit_code(), 14);
+}
+
+#[test]
+fn test_catalog_scan_with_file() {
+    let file = "target/catalog_patterns.toml";
+    std::fs::write(file, r#"
+[[pattern]]
+id = "forex-unused"
+languages = ["forex"]
+severity = "error"
+regex = 'ЗHAЧEH\. .* HE ИCПOЛЬЗYETCЯ'
+description = "Unused value is an error here"
+"#).unwrap();
+    let catalog = Catalog::load(file).unwrap();
+    let units = vec![unit("target/catalog_unused.forex", "        program test
+        a = 1
+        end
+")];
+    let listing = "                F O R E X
+                  PROGRAM TEST
+                  A = 1
+                  END
+ ЗHAЧEH. A       HE ИCПOЛЬЗYETCЯ
+";
+    let diagnostics = scan_listing_with(&catalog, listing, &units, "target/listing.lst");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].description, "Unused value is an error here");
+
+    let options = parse_and_process(vec!["besmc", "--patterns", file, "hello.forex"]);
+    assert_eq!(options.patterns.as_deref(), Some(file));
+}
@@ -0,0 +1,87 @@
+use crate::test::*;
+
+fn diagnostic(file: &str, line: Option<usize>, column: Option<usize>) -> Diagnostic {
+    Diagnostic {
+        file: file.to_string(),
+        line,
+        column,
+        severity: Severity::Error,
+        message: "HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR".to_string(),
+        language: Some(Language::Madlen),
+        listing_line: 4,
+        text: " ****** HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR".to_string(),
+        rule: "madlen-undeclared".to_string(),
+        description: "Undeclared identifier".to_string(),
+    }
+}
+
+#[test]
+fn test_diagnostics_text() {
+    let diagnostics = vec![diagnostic("hello.madlen", Some(2), Some(14))];
+    let report = format_diagnostics(DiagnosticsFormat::Text, &diagnostics);
+
+    assert_eq!(report, "hello.madlen:2:14: error: HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR\n");
+}
+
+#[test]
+fn test_diagnostics_json() {
+    let diagnostics = vec![diagnostic("hello.madlen", Some(2), None)];
+    let report = format_diagnostics(DiagnosticsFormat::Json, &diagnostics);
+    let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["severity"], "error");
+    assert_eq!(json[0]["language"], "Madlen-3.5");
+    assert_eq!(json[0]["file"], "hello.madlen");
+    assert_eq!(json[0]["line"], 2);
+    assert!(json[0]["column"].is_null());
+    assert_eq!(json[0]["listing_line"], 4);
+    assert_eq!(json[0]["text"], " ****** HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR");
+    assert_eq!(json[0]["message"], "HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR");
+    assert_eq!(json[0]["rule"], "madlen-undeclared");
+}
+
+#[test]
+fn test_diagnostics_sarif() {
+    let diagnostics = vec![
+        diagnostic("hello.madlen", Some(2), Some(14)),
+        diagnostic("hello.lst", None, None),
+    ];
+    let report = format_diagnostics(DiagnosticsFormat::Sarif, &diagnostics);
+    let sarif: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "besmc");
+    assert_eq!(run["results"].as_array().unwrap().len(), 2);
+    assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 1);
+    assert_eq!(run["results"][0]["ruleId"], "madlen-undeclared");
+
+    let location = &run["results"][0]["locations"][0]["physicalLocation"];
+    assert_eq!(run["results"][0]["level"], "error");
+    assert_eq!(location["artifactLocation"]["uri"], "hello.madlen");
+    assert_eq!(location["region"]["startLine"], 2);
+    assert_eq!(location["region"]["startColumn"], 14);
+
+    let location = &run["results"][1]["locations"][0]["physicalLocation"];
+    assert!(location.get("region").is_none());
+}
+
+#[test]
+fn test_diagnostics_empty() {
+    assert_eq!(format_diagnostics(DiagnosticsFormat::Text, &[]), "");
+    assert_eq!(format_diagnostics(DiagnosticsFormat::Json, &[]), "[]\n");
+
+    let report = format_diagnostics(DiagnosticsFormat::Sarif, &[]);
+    let sarif: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(sarif["runs"][0]["results"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_diagnostics_format_option() {
+    let options = parse_and_process(vec!["besmc", "--diagnostics-format=sarif", "hello.ftn"]);
+    assert_eq!(options.diagnostics_format, DiagnosticsFormat::Sarif);
+
+    let options = parse_and_process(vec!["besmc", "hello.ftn"]);
+    assert_eq!(options.diagnostics_format, DiagnosticsFormat::Text);
+}
@@ -0,0 +1,83 @@
+use crate::test::*;
+
+#[test]
+fn test_disasm_instructions() {
+    assert_eq!(disassemble_instruction(0o42474001), "10,vtm,74001");
+    assert_eq!(disassemble_instruction(0o03000000), ",uj,");
+    assert_eq!(disassemble_instruction(0o00740000), ",*74,");
+    assert_eq!(disassemble_instruction(0o00100012), ",xta,12");
+    assert_eq!(disassemble_instruction(0o41000025), "10,atx,70025");
+    assert_eq!(disassemble_instruction(0o73300000), "16,stop,");
+}
+
+#[test]
+fn test_disasm_word() {
+    assert_eq!(disassemble_word(0o4247400103074002), ("10,vtm,74001".to_string(), ",uj,74002".to_string()));
+}
+
+//
+// Header and tables of the module are described, not decoded as instructions;
+// text constants are shown.
+//
+#[test]
+fn test_disasm_stdarray() {
+    let array = StdArray::load("examples/stdarray.std").unwrap();
+    let listing = disassemble(&array);
+    let lines: Vec<&str> = listing.lines().collect();
+
+    assert_eq!(lines[0], "Module PROGRAM: 4 records, 64 words");
+    assert_eq!(lines[1], "Entries: MAIN at L00016, PROGRAM at L00016");
+    assert_eq!(lines[2], "00000  5541515600000000  entry MAIN");
+    assert_eq!(lines[2 + 0o4], "00004  0000000000000001  passport");
+    assert_eq!(lines[2 + 0o16], "00016  4247400103074002  10,vtm,L00043     ,uj,L00032");
+    assert_eq!(lines[2 + 0o45], "00045  2204251423047454  \"HELLO,\"");
+    assert_eq!(lines[2 + 0o57], "00057  0000401667000036  symbol 4: common *OUTPUT*, 36 words");
+    assert_eq!(lines[2 + 0o72], "00072  6017605544634564  long name P/PMDSET");
+    assert_eq!(lines.len(), 66);
+}
+
+//
+// Calls of external routines and addresses of common blocks are shown by name,
+// and addresses in the module by their place in the listing.
+//
+#[test]
+fn test_disasm_external_call() {
+    let array = StdArray::load("examples/stdarray.std").unwrap();
+    let words: Vec<u64> = array.modules[0].words().collect();
+    let layout = module_layout(&words).unwrap();
+
+    assert_eq!(layout.code, 0o16..0o43);
+    assert_eq!(layout.constants, 0o43..0o53);
+    assert_eq!(layout.table, 0o53..0o73);
+    assert_eq!(layout.symbols[9], ModuleSymbol { name: "P/WL".to_string(), description: "external P/WL".to_string() });
+    assert_eq!(layout.symbols[7].name, "L00045");
+
+    let listing = disassemble(&array);
+    let lines: Vec<&str> = listing.lines().collect();
+    assert_eq!(lines[2 + 0o30], "00030  6247400467174012  14,vtm,*OUTPUT*   15,vjm,P/WL");
+    assert_eq!(lines[2 + 0o26], "00026  5647401052400016  13,vtm,L00045     12,vtm,16");
+    assert_eq!(lines[2 + 0o33], "00033  0010000067140001  ,xta,             15,vjm,L00017");
+}
+
+//
+// Module of unknown layout is shown as instructions, word by word.
+//
+#[test]
+fn test_disasm_unknown_layout() {
+    let array = StdArray::parse("`77761 CODE     1\n`4247400103074002\n`2204251423047454\n").unwrap();
+    let listing = disassemble(&array);
+
+    assert_eq!(listing, "\
+Module CODE: 1 records, 2 words
+00000  4247400103074002  10,vtm,74001      ,uj,74002
+00001  2204251423047454  4,*20,42514       4,uj,47454        \"HELLO,\"
+");
+}
+
+#[test]
+fn test_disasm_not_stdarray() {
+    let options = parse_and_process(vec!["besmc", "disasm", "examples/hello.ftn"]);
+    let err = Build::new(options).run().unwrap_err();
+
+    assert_eq!(err.exit_code(), 17);
+}
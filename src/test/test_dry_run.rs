@@ -0,0 +1,305 @@
+use crate::test::*;
+
+//
+// Plan of compiling a Fortran program into executable.
+//
+#[test]
+fn test_plan_ftn_exe() {
+    let options = CompilerOptions {
+        output_file: Some("target/plan_ftn.exe".to_string()),
+        files: vec!["examples/hello.ftn".to_string()],
+        ..Default::default()
+    };
+    let plan = Build::new(options).plan().unwrap();
+
+    assert!(plan.commands.is_empty());
+    assert_eq!(plan.script_name, "plan_ftn.dub");
+    assert_eq!(plan.listing, "target/plan_ftn.lst");
+    assert_eq!(plan.script_text(), "*name compile
+*disc:1/local
+*file:output,60,w
+*call setftn:one,long
+*ftn
+        program hello
+        print 1000
+        stop
+ 1000   format('Hello, Ftn!')
+        end
+*library:22
+*call overlay
+program
+*end record
+*end file
+");
+}
+
+//
+// Object files are mounted as perso disks.
+//
+#[test]
+fn test_plan_link_objects() {
+    let options = CompilerOptions {
+        files: vec!["target/caller.obj".to_string(), "target/callee.obj".to_string()],
+        ..Default::default()
+    };
+    let plan = Build::new(options).plan().unwrap();
+
+    assert_eq!(plan.copies, vec![
+        ("target/caller.obj".to_string(), "$WORKDIR/pers40.bin".to_string()),
+        ("target/callee.obj".to_string(), "$WORKDIR/pers41.bin".to_string())]);
+    let script = plan.script_text();
+    assert!(script.contains("*file:pers40,40\n*file:pers41,41\n"));
+    assert!(script.contains("*call perso:40,cont\n*call perso:41,cont\n*library:22\n"));
+}
+
+//
+// Pascal-re source is compiled by pascompl before Dubna runs.
+//
+#[test]
+fn test_plan_pas_obj() {
+    let options = CompilerOptions {
+        stop_at_object: true,
+        files: vec!["examples/hello.pas".to_string()],
+        ..Default::default()
+    };
+    let plan = Build::new(options).plan().unwrap();
+
+    assert_eq!(plan.commands.len(), 1);
+    assert_eq!(plan.commands[0].to_string(), "pascompl -P examples/hello.pas $WORKDIR/hello.std");
+    assert_eq!(plan.output, "examples/hello.obj");
+    assert!(plan.script.contains(&ScriptPart::Include("$WORKDIR/hello.std".to_string())));
+    assert!(plan.script_text().ends_with("<contents of $WORKDIR/hello.std>\n*call to perso: 60\n*end file\n"));
+}
+
+//
+// Many object files are merged into intermediate libraries,
+// so that the last job loads no more than 16 of them.
+//
+#[test]
+fn test_plan_many_objects() {
+    let files = (0..17).map(|i| format!("target/many{}.obj", i)).collect();
+    let options = CompilerOptions {
+        files,
+        ..Default::default()
+    };
+    let plan = Build::new(options).plan().unwrap();
+
+    assert_eq!(plan.copies, vec![
+        ("$WORKDIR/merged-0-00.obj".to_string(), "$WORKDIR/pers40.bin".to_string()),
+        ("target/many16.obj".to_string(), "$WORKDIR/pers41.bin".to_string())]);
+}
+
+//
+// With -c and -j, every source is compiled by its own job into the work directory,
+// and the last job merges the objects, given ones included, into the output.
+//
+#[test]
+fn test_plan_parallel_jobs() {
+    let options = parse_and_process(vec!["besmc", "--dry-run", "-c", "-j", "4", "-o", "target/parallel.obj",
+                                         "examples/hello.ftn", "target/parallel_lib.obj", "examples/hello.algol"]);
+    let plans = Build::new(options).plans().unwrap();
+
+    let jobs: Vec<(&str, &str)> = plans.iter().map(|plan| (plan.output.as_str(), plan.listing.as_str())).collect();
+    assert_eq!(jobs, [
+        ("$WORKDIR/00-hello.obj", "$WORKDIR/00-hello.lst"),
+        ("$WORKDIR/02-hello.obj", "$WORKDIR/02-hello.lst"),
+        ("target/parallel.obj", "$WORKDIR/merge.lst"),
+    ]);
+    assert!(plans[0].script_text().contains("*ftn\n"));
+    assert!(plans[1].script_text().contains("*algol\n"));
+    assert_eq!(plans[2].copies, vec![
+        ("$WORKDIR/00-hello.obj".to_string(), "$WORKDIR/pers40.bin".to_string()),
+        ("target/parallel_lib.obj".to_string(), "$WORKDIR/pers41.bin".to_string()),
+        ("$WORKDIR/02-hello.obj".to_string(), "$WORKDIR/pers42.bin".to_string())]);
+    assert!(plans[2].to_string().contains("(cd $WORKDIR && dubna parallel.dub) > $WORKDIR/merge.lst\n"));
+}
+
+//
+// With -j, the last job merges more than 16 objects by merge jobs first:
+// merged-ROUND-GROUP.obj, one per 16 objects.
+//
+#[test]
+fn test_plan_parallel_merges() {
+    let mut args = vec!["besmc", "--dry-run", "-c", "-j", "2", "-o", "target/parallel_many.obj", "examples/hello.ftn"];
+    let objects: Vec<String> = (0..17).map(|i| format!("target/many{}.obj", i)).collect();
+    args.extend(objects.iter().map(|file| file.as_str()));
+    let plans = Build::new(parse_and_process(args)).plans().unwrap();
+
+    let outputs: Vec<&str> = plans.iter().map(|plan| plan.output.as_str()).collect();
+    assert_eq!(outputs, ["$WORKDIR/00-hello.obj", "$WORKDIR/merged-0-00.obj", "$WORKDIR/merged-0-01.obj", "target/parallel_many.obj"]);
+    assert_eq!(plans[1].listing, "$WORKDIR/merged-0-00.lst");
+    assert_eq!(plans[1].copies.len(), 16);
+    assert_eq!(plans[1].copies[0].0, "$WORKDIR/00-hello.obj");
+    assert_eq!(plans[2].copies, vec![
+        ("target/many15.obj".to_string(), "$WORKDIR/pers40.bin".to_string()),
+        ("target/many16.obj".to_string(), "$WORKDIR/pers41.bin".to_string())]);
+    assert_eq!(plans[3].copies, vec![
+        ("$WORKDIR/merged-0-00.obj".to_string(), "$WORKDIR/pers40.bin".to_string()),
+        ("$WORKDIR/merged-0-01.obj".to_string(), "$WORKDIR/pers41.bin".to_string())]);
+}
+
+//
+// With --split-objects, each source gets its own job and object, named after it:
+// in the current directory, or in the directory given by -o.
+// Object files given as input are not compiled.
+//
+#[test]
+fn test_plan_split_objects() {
+    std::fs::create_dir_all("target/split_dir").unwrap();
+    std::fs::create_dir_all("target/split_src").unwrap();
+    std::fs::copy("examples/hello.ftn", "target/split_src/first.ftn").unwrap();
+    std::fs::copy("examples/hello.algol", "target/split_src/second.algol").unwrap();
+
+    let options = parse_and_process(vec!["besmc", "--dry-run", "-c", "--split-objects",
+                                         "target/split_src/first.ftn", "target/split_lib.obj", "target/split_src/second.algol"]);
+    let plans = Build::new(options).plans().unwrap();
+
+    let jobs: Vec<(&str, &str)> = plans.iter().map(|plan| (plan.output.as_str(), plan.listing.as_str())).collect();
+    assert_eq!(jobs, [("first.obj", "first.lst"), ("second.obj", "second.lst")]);
+    assert!(plans[0].script_text().contains("*ftn\n"));
+    assert!(plans[1].script_text().contains("*algol\n"));
+
+    let options = parse_and_process(vec!["besmc", "--dry-run", "-c", "--split-objects", "--emit=std", "-o", "target/split_dir",
+                                         "target/split_src/first.ftn", "target/split_src/second.algol"]);
+    let plans = Build::new(options).plans().unwrap();
+
+    let jobs: Vec<(&str, &str)> = plans.iter().map(|plan| (plan.output.as_str(), plan.listing.as_str())).collect();
+    assert_eq!(jobs, [("target/split_dir/first.std", "target/split_dir/first.lst"),
+                      ("target/split_dir/second.std", "target/split_dir/second.lst")]);
+    assert!(plans.iter().all(|plan| plan.punched));
+}
+
+//
+// Hundreds of object files need more than one round of merging.
+// Sources keep their place, merged libraries replace the first object.
+//
+#[test]
+fn test_plan_merge_rounds() {
+    let mut files: Vec<String> = (0..300).map(|i| format!("target/many{}.obj", i)).collect();
+    files.insert(0, "examples/hello.ftn".to_string());
+    let options = CompilerOptions {
+        files,
+        ..Default::default()
+    };
+    let plan = Build::new(options).plan().unwrap();
+
+    // 300 objects make 19 libraries in the first round, and 2 in the second.
+    assert_eq!(plan.copies, vec![
+        ("$WORKDIR/merged-1-00.obj".to_string(), "$WORKDIR/pers40.bin".to_string()),
+        ("$WORKDIR/merged-1-01.obj".to_string(), "$WORKDIR/pers41.bin".to_string())]);
+    assert!(plan.script_text().contains("*ftn\n"));
+    assert_eq!(plan.output, "examples/hello.exe");
+}
+
+//
+// Libraries given by -l are found in -L directories and searched by the loader.
+//
+#[test]
+fn test_plan_named_libraries() {
+    std::fs::create_dir_all("target/plan_libs").unwrap();
+    std::fs::write("target/plan_libs/libutil.obj", "").unwrap();
+    let options = parse_and_process(vec!["besmc", "-L", "target/nowhere", "-Ltarget/plan_libs", "-lutil",
+                                         "-o", "target/plan_libs.exe", "examples/hello.ftn"]);
+    let plan = Build::new(options).plan().unwrap();
+
+    let script = plan.script_text();
+    assert!(script.starts_with("*name compile\n*disc:1/local\n*file:output,60,w\n*file:lib61,61\n"));
+    assert!(script.contains("*library:61\n*library:22\n*call overlay\n"));
+    assert_eq!(plan.symlinks.len(), 1);
+    assert!(plan.symlinks[0].0.ends_with("target/plan_libs/libutil.obj"));
+    assert_eq!(plan.symlinks[0].1, "$WORKDIR/lib61.bin");
+    assert_eq!(plan.libraries[0], Library {
+        directive: "*library:61".to_string(),
+        origin: "target/plan_libs/libutil.obj".to_string(),
+    });
+}
+
+//
+// Missing library is reported with the places where it was looked for.
+//
+#[test]
+fn test_plan_library_not_found() {
+    let options = parse_and_process(vec!["besmc", "-L", "target/nowhere", "-lmissing", "examples/hello.ftn"]);
+    let err = Build::new(options).plan().unwrap_err();
+
+    assert_eq!(err.exit_code(), 16);
+    assert!(err.to_string().starts_with("Library -lmissing not found. Looked in:\n  target/nowhere/libmissing.obj\n"));
+}
+
+//
+// Libraries are mounted as disks 61 to 77, so at most 15 of them can be given.
+//
+#[test]
+fn test_plan_too_many_libraries() {
+    let mut args = vec!["besmc".to_string()];
+    args.extend((1..=16).map(|n| format!("-lutil{}", n)));
+    args.push("examples/hello.ftn".to_string());
+    let options = CompilerOptions::try_parse_from(args).unwrap();
+    let err = Build::new(options).plan().unwrap_err();
+
+    assert_eq!(err.exit_code(), 24);
+    assert_eq!(err.to_string(), "Cannot link 16 libraries given by -l: at most 15 are allowed");
+}
+
+//
+// With --emit=std, compiled modules are punched to the listing as a standard array.
+//
+#[test]
+fn test_plan_emit_std() {
+    let options = parse_and_process(vec!["besmc", "-c", "--emit=std", "-o", "target/plan_emit", "examples/hello.ftn"]);
+    let plan = Build::new(options).plan().unwrap();
+
+    assert_eq!(plan.output, "target/plan_emit.std");
+    assert!(plan.punched);
+    let script = plan.script_text();
+    assert!(script.starts_with("*name compile\n*disc:1/local\n*file:output,60,w\n*punch\n*call setftn:one,long\n"));
+    assert!(script.ends_with("        end\n*end file\n"));
+    assert!(plan.to_string().ends_with("grep '^`' target/plan_emit.lst > target/plan_emit.std\n"));
+}
+
+#[test]
+fn test_emit_needs_compile() {
+    let result = CompilerOptions::try_parse_from(vec!["besmc", "--emit=std", "examples/hello.ftn"]);
+    assert!(result.is_err());
+}
+
+//
+// Dry run prints the job as a shell script: the Dubna script, and the commands
+// which run it and make the executable.
+//
+#[test]
+fn test_dry_run_output() {
+    let options = parse_and_process(vec!["besmc", "-v", "--dry-run", "-o", "target/verbose.exe", "examples/hello.ftn"]);
+    let plan = Build::new(options).plan().unwrap();
+
+    assert_eq!(plan.to_string(), "\
+cat > $WORKDIR/verbose.dub << 'EOF'
+*name compile
+*disc:1/local
+*file:output,60,w
+*call setftn:one,long
+*ftn
+        program hello
+        print 1000
+        stop
+ 1000   format('Hello, Ftn!')
+        end
+*library:22
+*call overlay
+program
+*end record
+*end file
+EOF
+(cd $WORKDIR && dubna verbose.dub) > target/verbose.lst
+(echo '#!/usr/bin/env dubna' && cat $WORKDIR/output.bin) > target/verbose.exe
+chmod +x target/verbose.exe
+");
+
+    // Passes of compilers come first.
+    let options = parse_and_process(vec!["besmc", "--dry-run", "-o", "target/verbose_pas.exe", "examples/hello.pas"]);
+    let text = Build::new(options).plan().unwrap().to_string();
+
+    assert!(text.starts_with("pascompl -P examples/hello.pas $WORKDIR/hello.std\ncat > $WORKDIR/verbose_pas.dub << 'EOF'\n"));
+    assert!(text.contains("*call setftn:one,long\n<contents of $WORKDIR/hello.std>\n*library:22\n"));
+    assert!(text.contains("\n(cd $WORKDIR && dubna verbose_pas.dub) > target/verbose_pas.lst\n"));
+}
@@ -0,0 +1,142 @@
+use crate::test::*;
+
+#[test]
+fn test_entry_of_examples() {
+    let entry = |file: &str| defined_entry(file).unwrap();
+
+    assert_eq!(entry("examples/hello.ftn").as_deref(), Some("program"));
+    assert_eq!(entry("examples/hello.pascal").as_deref(), Some("program"));
+    assert_eq!(entry("examples/hello.algol").as_deref(), Some("program"));
+    assert_eq!(entry("examples/hello.madlen").as_deref(), Some("program"));
+    assert_eq!(entry("examples/hello.bemsh").as_deref(), Some("main"));
+    assert_eq!(entry("examples/hello.b").as_deref(), Some("program"));
+    assert_eq!(entry("examples/hello.c").as_deref(), Some("program"));
+    assert_eq!(entry("examples/stdarray.std").as_deref(), Some("program"));
+    assert_eq!(entry("examples/callee.ftn"), None);
+}
+
+//
+// Entry of БЕМШ module is found in object code, and not only in the first file.
+//
+#[test]
+fn test_entry_from_object() {
+    let mut bytes = vec![0o17; 12];
+    bytes.extend_from_slice(&0o5541515600000000u64.to_be_bytes()[2..]);
+    std::fs::write("target/entry_main.obj", bytes).unwrap();
+    let files = vec!["examples/callee.ftn".to_string(), "target/entry_main.obj".to_string()];
+
+    assert_eq!(find_entry(&files).unwrap(), "main");
+}
+
+#[test]
+fn test_entry_errors() {
+    let err = find_entry(&["examples/hello.ftn".to_string(), "examples/hello.bemsh".to_string()]).unwrap_err();
+    assert_eq!(err.exit_code(), 20);
+    assert_eq!(err.to_string(), "Several inputs define an entry:
+  examples/hello.ftn: program
+  examples/hello.bemsh: main
+Use -e NAME to choose one");
+
+    let err = find_entry(&["examples/callee.ftn".to_string()]).unwrap_err();
+    assert_eq!(err.exit_code(), 19);
+    assert_eq!(err.to_string(), "No input defines an entry:
+  examples/callee.ftn
+Use -e NAME to give one");
+}
+
+//
+// Option -e overrides the entry found in the inputs.
+//
+#[test]
+fn test_entry_option() {
+    let options = parse_and_process(vec!["besmc", "-e", "start", "examples/callee.ftn"]);
+    let plan = Build::new(options).plan().unwrap();
+
+    assert!(plan.script_text().ends_with("*call overlay\nstart\n*end record\n*end file\n"));
+}
+
+//
+// Main program need not be the first file.
+//
+#[test]
+fn test_entry_not_first() {
+    let options = CompilerOptions {
+        files: vec!["examples/callee.ftn".to_string(), "examples/hello.bemsh".to_string()],
+        ..Default::default()
+    };
+    let plan = Build::new(options).plan().unwrap();
+
+    assert!(plan.script_text().ends_with("*call overlay\nmain\n*end record\n*end file\n"));
+}
+
+//
+// When no input defines an entry, program is assumed, and the compilers
+// get to report what is wrong with the sources.
+//
+#[test]
+fn test_entry_not_found() {
+    std::fs::write("target/entry_no_header.madlen", "        ,end,\n").unwrap();
+    let options = parse_and_process(vec!["besmc", "target/entry_no_header.madlen"]);
+    let plan = Build::new(options).plan().unwrap();
+
+    assert!(plan.entry_assumed);
+    assert!(plan.script_text().ends_with("*call overlay\nprogram\n*end record\n*end file\n"));
+}
+
+//
+// When the sources compile, the missing entry is reported with the inputs.
+//
+#[test]
+fn test_entry_missing() {
+    let options = CompilerOptions {
+        output_file: Some("target/entry_missing.exe".to_string()),
+        files: vec!["examples/callee.ftn".to_string()],
+        ..Default::default()
+    };
+    let err = Build::new(options).run().unwrap_err();
+
+    assert!(matches!(&err, BesmcError::NoEntry { files } if files == &["examples/callee.ftn"]));
+    assert_eq!(err.exit_code(), 19);
+}
+
+//
+// Madlen module main is not an entry: the loader looks for program.
+//
+#[test]
+fn test_entry_assem_main() {
+    std::fs::write("target/entry_main.assem", " main: ,name,\n        ,*74,\n        ,end,\n").unwrap();
+
+    assert_eq!(defined_entry("target/entry_main.assem").unwrap(), None);
+}
+
+//
+// Fortran subprogram indented by a tab is not a main program.
+//
+#[test]
+fn test_entry_fortran_tab() {
+    std::fs::write("target/entry_tab.ftn", "\tsubroutine foo\n\tend\n").unwrap();
+    std::fs::write("target/entry_tab_main.ftn", "\tprogram foo\n\tend\n").unwrap();
+    let files = vec!["examples/hello.ftn".to_string(), "target/entry_tab.ftn".to_string()];
+
+    assert_eq!(defined_entry("target/entry_tab.ftn").unwrap(), None);
+    assert_eq!(defined_entry("target/entry_tab_main.ftn").unwrap().as_deref(), Some("program"));
+    assert_eq!(find_entry(&files).unwrap(), "program");
+}
+
+//
+// Object code which mentions both PROGRAM and MAIN, like a call from Pascal,
+// does not make several entries.
+//
+#[test]
+fn test_entry_object_hint() {
+    let mut bytes = 0o6062574762415500u64.to_be_bytes()[2..].to_vec();
+    bytes.extend_from_slice(&0o5541515600000000u64.to_be_bytes()[2..]);
+    std::fs::write("target/entry_both.obj", &bytes).unwrap();
+    std::fs::write("target/entry_other.obj", &bytes[6..]).unwrap();
+
+    let files = vec!["target/entry_both.obj".to_string(), "examples/hello.bemsh".to_string()];
+    assert_eq!(find_entry(&files).unwrap(), "main");
+
+    let files = vec!["target/entry_both.obj".to_string(), "target/entry_other.obj".to_string()];
+    assert!(matches!(find_entry(&files), Err(BesmcError::NoEntry { .. })));
+}
@@ -8,7 +8,7 @@ fn test_b_exe() {
         files: vec!["examples/hello.b".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_b.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  001 30");
 }
@@ -20,7 +20,7 @@ fn test_algol_exe() {
         files: vec!["examples/hello.algol".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_algol.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  001 17");
 }
@@ -32,7 +32,7 @@ fn test_assem_exe() {
         files: vec!["examples/hello.assem".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_assem.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  001 01");
 }
@@ -44,7 +44,7 @@ fn test_bemsh_exe() {
         files: vec!["examples/hello.bemsh".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_bemsh.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  001 01");
 }
@@ -56,7 +56,7 @@ fn test_forex_exe() {
         files: vec!["examples/hello.forex".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_forex.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  002 30");
 }
@@ -68,7 +68,7 @@ fn test_fortran_exe() {
         files: vec!["examples/hello.fortran".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_fortran.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  002 30");
 }
@@ -80,7 +80,7 @@ fn test_ftn_exe() {
         files: vec!["examples/hello.ftn".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_ftn.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  002 30");
 }
@@ -92,7 +92,7 @@ fn test_madlen_exe() {
         files: vec!["examples/hello.madlen".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_madlen.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  001 01");
 }
@@ -104,7 +104,7 @@ fn test_pascal_exe() {
         files: vec!["examples/hello.pascal".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_pascal.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  002 17");
 }
@@ -116,7 +116,7 @@ fn test_pas_exe() {
         files: vec!["examples/hello.pas".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_pas.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  002 17");
 }
@@ -128,7 +128,7 @@ fn test_c_exe() {
         files: vec!["examples/hello.c".to_string()],
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_c.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  003 03");
 }
@@ -0,0 +1,75 @@
+use crate::test::*;
+
+#[test]
+fn test_golden_compare() {
+    assert_eq!(compare_output("HELLO\n", "HELLO\n"), None);
+    assert_eq!(compare_output("HELLO\nWORLD\n", "HELLO\nW0RLD\n").unwrap(),
+               "output differs at line 2\n    expected: WORLD\n    actual:   W0RLD");
+    assert_eq!(compare_output("HELLO\n", "").unwrap(),
+               "output differs at line 1\n    expected: HELLO\n    actual:   <end of output>");
+}
+
+//
+// Programs are sources in the directory, in order of names;
+// input, expected output and object files are not programs.
+//
+#[test]
+fn test_golden_programs() {
+    let dir = "target/golden_programs";
+    std::fs::create_dir_all(dir).unwrap();
+    for file in ["b.pascal", "a.ftn", "a.input", "a.expected", "lib.obj"] {
+        std::fs::write(format!("{}/{}", dir, file), "").unwrap();
+    }
+    let programs = find_programs(dir).unwrap();
+
+    assert_eq!(programs, vec!["target/golden_programs/a.ftn", "target/golden_programs/b.pascal"]);
+}
+
+#[test]
+fn test_golden_result() {
+    let result = TestResult {
+        program: "suite/a.ftn".to_string(),
+        outcome: TestOutcome::Failed("program failed with status: exit status: 1".to_string()),
+    };
+    assert_eq!(result.to_string(), "FAIL    suite/a.ftn: program failed with status: exit status: 1");
+}
+
+#[test]
+fn test_golden_empty_directory() {
+    std::fs::create_dir_all("target/golden_empty").unwrap();
+    let options = parse_and_process(vec!["besmc", "test", "target/golden_empty"]);
+    let err = Build::new(options).run().unwrap_err();
+
+    assert_eq!(err.exit_code(), 11);
+}
+
+//
+// Programs are shown in verbose and dry run output the same way as compiler passes:
+// with the directory they run in, and redirections.
+//
+#[test]
+fn test_golden_command_line() {
+    let command = ToolCommand::new("suite/a.exe", &[], "suite/a.ftn");
+    assert_eq!(command.command_line(std::path::Path::new("/work"), Some("suite/a.input"), None),
+               "cd /work && suite/a.exe < suite/a.input");
+
+    let command = ToolCommand::new("dubna", &["job.dub"], "job.dub");
+    assert_eq!(command.command_line(std::path::Path::new("/tmp/besmc 1"), None, Some("/work/a.lst")),
+               "cd '/tmp/besmc 1' && dubna job.dub > /work/a.lst");
+}
+
+//
+// Dry run builds nothing and runs nothing.
+//
+#[test]
+fn test_golden_dry_run() {
+    let dir = "target/golden_dry_run";
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(format!("{}/a.ftn", dir), "").unwrap();
+    std::fs::write(format!("{}/a.input", dir), "").unwrap();
+    let options = parse_and_process(vec!["besmc", "--dry-run", "test", dir]);
+    Build::new(options).run().unwrap();
+
+    assert!(!std::path::Path::new("target/golden_dry_run/a.exe").exists());
+    assert!(!std::path::Path::new("target/golden_dry_run/a.lst").exists());
+}
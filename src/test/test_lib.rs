@@ -0,0 +1,161 @@
+use crate::test::*;
+
+//
+// Options before and after the action apply to Dubna jobs of the subcommand.
+//
+#[test]
+fn test_lib_options() {
+    let options = parse_and_process(vec!["besmc", "-v", "lib", "add", "--dry-run", "foo.obj", "bar.ftn", "baz.pascal"]);
+
+    assert!(options.verbose);
+    assert!(options.dry_run);
+    assert!(options.files.is_empty());
+    assert_eq!(options.command, Some(Command::Lib {
+        action: LibAction::Add {
+            library: "foo.obj".to_string(),
+            files: vec!["bar.ftn".to_string(), "baz.pascal".to_string()],
+        },
+    }));
+}
+
+#[test]
+fn test_lib_add_needs_files() {
+    let result = CompilerOptions::try_parse_from(vec!["besmc", "lib", "add", "foo.obj"]);
+    assert!(result.is_err());
+}
+
+//
+// Modules are listed from the punched library, with entries from their headers;
+// the date comes from the listing of the punch job.
+//
+#[test]
+fn test_lib_contents() {
+    let array = StdArray::load("examples/stdarray.std").unwrap();
+    let contents = library_contents("target/util.obj", &array, &listing_excerpt("caller.pascal"));
+
+    assert_eq!(contents.modules, vec![LibraryModule {
+        name: "PROGRAM".to_string(),
+        words: 0o35,
+        entries: vec![("MAIN".to_string(), 0o16), ("PROGRAM".to_string(), 0o16)],
+    }]);
+    assert_eq!(contents.to_string(), "\
+Library target/util.obj, created 14/04/25 00.28.36
+  Module     Octal  Decimal  Entries
+  PROGRAM       35       29  MAIN at 00016, PROGRAM at 00016
+");
+}
+
+//
+// List a library made by lib add.
+//
+#[test]
+fn test_lib_list() {
+    let library = "target/lib_list.obj";
+    let _ = std::fs::remove_file(library);
+    Build::new(parse_and_process(vec!["besmc", "lib", "add", library, "examples/callee.ftn"])).run().unwrap();
+    let (contents, _) = list_library(library, &CompilerOptions::default()).unwrap();
+
+    let names: Vec<&str> = contents.modules.iter().map(|module| module.name.as_str()).collect();
+    assert_eq!(names, ["HELLO"]);
+    assert!(contents.modules[0].entries.iter().any(|(name, _)| name == "HELLO"));
+    assert!(contents.modules[0].words > 0);
+    assert!(contents.created.is_some());
+}
+
+//
+// Library is not touched by a dry run.
+//
+#[test]
+fn test_lib_add_dry_run() {
+    let options = parse_and_process(vec!["besmc", "--dry-run", "lib", "add", "target/lib_new.obj", "examples/hello.ftn"]);
+    let artifacts = Build::new(options).run().unwrap();
+
+    assert_eq!(artifacts.listing, "target/lib_new.lst");
+    assert!(!std::path::Path::new("target/lib_new.obj").exists());
+}
+
+#[test]
+fn test_lib_replace_missing_library() {
+    let options = parse_and_process(vec!["besmc", "lib", "replace", "target/lib_missing.obj", "examples/hello.ftn"]);
+    let err = Build::new(options).run().unwrap_err();
+
+    assert_eq!(err.exit_code(), 12);
+}
+
+//
+// Extracted module is loaded back from a standard array into its own library.
+//
+#[test]
+fn test_lib_extract_dry_run() {
+    let options = parse_and_process(vec!["besmc", "--dry-run", "-o", "target/lib_hello.obj", "lib", "extract", "target/lib_extract.obj", "HELLO"]);
+    let artifacts = Build::new(options).run().unwrap();
+
+    assert_eq!(artifacts.listing, "target/lib_hello.lst");
+    assert!(!std::path::Path::new("target/lib_hello.obj").exists());
+}
+
+//
+// New version takes the place of the old module, and no old copy is left.
+//
+#[test]
+fn test_lib_replace_drops_old_module() {
+    let old = StdArray::parse("`77761 HELLO    1\n`0000000000000001\n`77761 MAIN     1\n`0000000000000002\n").unwrap();
+    let new = StdArray::parse("`77761 HELLO    1\n`0000000000000003\n`77761 WORLD    1\n`0000000000000004\n").unwrap();
+    let mut library = old.clone();
+    library.replace_modules(new);
+
+    assert_eq!(library.to_string(), "\
+`77761 HELLO    1
+`0000000000000003
+`77761 MAIN     1
+`0000000000000002
+`77761 WORLD    1
+`0000000000000004
+");
+    assert_eq!(library.module("hello").unwrap().words().collect::<Vec<_>>(), vec![3]);
+}
+
+//
+// Replace a module of a real library, and punch it to see what is left.
+//
+#[test]
+fn test_lib_replace() {
+    let library = "target/lib_replace.obj";
+    let _ = std::fs::remove_file(library);
+    let run = |args: Vec<&str>| Build::new(parse_and_process(args)).run().unwrap();
+    run(vec!["besmc", "lib", "add", library, "examples/callee.ftn"]);
+    run(vec!["besmc", "lib", "replace", library, "examples/callee.ftn"]);
+    run(vec!["besmc", "convert", library, "target/lib_replace.std"]);
+
+    let array = StdArray::load("target/lib_replace.std").unwrap();
+    let names: Vec<_> = array.modules.iter().map(|module| module.name.as_str()).collect();
+    assert_eq!(names, vec!["HELLO"]);
+}
+
+#[test]
+fn test_lib_not_object() {
+    let options = parse_and_process(vec!["besmc", "lib", "list", "examples/hello.ftn"]);
+    let err = Build::new(options).run().unwrap_err();
+
+    assert_eq!(err.exit_code(), 8);
+}
+
+//
+// Object library is converted to a standard array by punching it.
+//
+#[test]
+fn test_convert_dry_run() {
+    let options = parse_and_process(vec!["besmc", "--dry-run", "convert", "target/convert.obj", "target/convert.std"]);
+    let artifacts = Build::new(options).run().unwrap();
+
+    assert_eq!(artifacts.listing, "target/convert.lst");
+    assert!(!std::path::Path::new("target/convert.std").exists());
+}
+
+#[test]
+fn test_convert_same_format() {
+    let options = parse_and_process(vec!["besmc", "convert", "target/convert.obj", "target/other.obj"]);
+    let err = Build::new(options).run().unwrap_err();
+
+    assert_eq!(err.exit_code(), 17);
+}
@@ -0,0 +1,103 @@
+use crate::test::*;
+
+fn plan_of(files: &[&str], output: &str) -> Plan {
+    let options = CompilerOptions {
+        output_file: Some(output.to_string()),
+        files: files.iter().map(|file| file.to_string()).collect(),
+        ..Default::default()
+    };
+    Build::new(options).plan().unwrap()
+}
+
+//
+// Plan of linking the mixed Pascal/Fortran example, from examples/README.md.
+//
+fn caller_plan() -> Plan {
+    plan_of(&["target/caller.obj", "target/callee.obj"], "target/caller.exe")
+}
+
+#[test]
+fn test_linkmap_libraries() {
+    let plan = caller_plan();
+
+    assert_eq!(plan.libraries, vec![
+        Library { directive: "*perso:40".to_string(), origin: "target/caller.obj".to_string() },
+        Library { directive: "*perso:41".to_string(), origin: "target/callee.obj".to_string() },
+        Library { directive: "*library:22".to_string(), origin: "system library".to_string() },
+    ]);
+}
+
+//
+// Link of an object library: the loader reports the library, and its modules
+// are found in the punched standard array. The loader does not print
+// where they are loaded.
+//
+#[test]
+fn test_linkmap_from_listing() {
+    let plan = plan_of(&["target/stdarray.obj"], "target/stdarray.exe");
+    let objects = vec![("target/stdarray.obj".to_string(), StdArray::load("examples/stdarray.std").unwrap())];
+    let map = build_link_map(&listing_excerpt("caller.pascal"), &plan, &objects).unwrap();
+
+    assert_eq!(map.libraries[0].created.as_deref(), Some("14/04/25 00.28.36"));
+    assert_eq!(map.libraries[1].created, None);
+    assert_eq!(map.modules, vec![
+        MapModule { name: "PROGRAM".to_string(), address: None, words: 0o35, origin: "target/stdarray.obj".to_string() },
+    ]);
+    assert_eq!(map.to_string(), "\
+Link map of target/stdarray.exe
+
+Libraries, in order of search:
+  *perso:40    target/stdarray.obj, created 14/04/25 00.28.36
+  *library:22  system library
+
+Modules:
+  Module   Address    Octal  Decimal  Origin
+  PROGRAM        -       35       29  target/stdarray.obj
+  Total                  35       29
+
+Library length: 004 03
+");
+}
+
+//
+// Modules which cannot be attributed to an input are an error, not an empty map.
+//
+#[test]
+fn test_linkmap_unattributed() {
+    let objects = vec![("target/caller.obj".to_string(), StdArray::load("examples/stdarray.std").unwrap())];
+    let err = build_link_map(&listing_excerpt("caller.pascal"), &caller_plan(), &objects).unwrap_err();
+
+    assert_eq!(err.exit_code(), 17);
+    assert_eq!(err.to_string(), "Cannot create link map of target/caller.exe: modules of target/callee.obj are not known");
+
+    // Module outside of the sections of the compilers.
+    let err = build_link_map(&listing_excerpt("hello.forex"), &caller_plan(), &[]).unwrap_err();
+    assert_eq!(err.to_string(), "Cannot create link map of target/caller.exe: module HELLO is not reported by a compiler");
+}
+
+//
+// БЕМШ reports the address of the module; it belongs to the source being assembled.
+//
+#[test]
+fn test_linkmap_address() {
+    let plan = plan_of(&["examples/hello.bemsh"], "target/hello_bemsh.exe");
+    let map = build_link_map(&listing_excerpt("hello.bemsh"), &plan, &[]).unwrap();
+
+    assert_eq!(map.modules, vec![
+        MapModule { name: "MAIN".to_string(), address: Some(0o1000), words: 0o10, origin: "examples/hello.bemsh".to_string() },
+    ]);
+    assert_eq!(map.library_length.as_deref(), Some("001 01"));
+}
+
+#[test]
+fn test_linkmap_sources() {
+    let plan = plan_of(&["examples/hello.forex"], "target/hello_forex.exe");
+    let map = build_link_map(&listing_excerpt("hello.forex"), &plan, &[]).unwrap();
+
+    assert_eq!(map.modules, vec![
+        MapModule { name: "HELLO".to_string(), address: None, words: 0o16, origin: "examples/hello.forex".to_string() },
+    ]);
+
+    let options = parse_and_process(vec!["besmc", "--map", "hello.map", "hello.forex"]);
+    assert_eq!(options.map.as_deref(), Some("hello.map"));
+}
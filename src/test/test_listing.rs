@@ -0,0 +1,144 @@
+use crate::test::*;
+
+#[test]
+fn test_listing_madlen_undefined_identifier() {
+    let units = vec![unit("target/listing_undefined.madlen", " program: ,name,
+        ,uj, foobar
+        ,end,
+")];
+    let listing = " PROGRAM           MADLEN-3.5
+ 001L               PROGRAM :  , NAME,
+ 0000                          , UJ  ,FOOBAR
+ ****** HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR
+ ЧИCЛO ПEPФ.     03      ЧИCЛO OШИБ. OПEPATOPOB  01
+ OШИБKИ HA CTP.    1.
+ БЫЛИ OШИБKИ ПPИ BBOДE ИЛИ TPAHCЛЯЦИИ !!!
+";
+    let diagnostics = scan_listing(listing, &units, "target/listing.lst");
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].to_string(),
+               "target/listing_undefined.madlen:2:14: error: HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR");
+    assert_eq!(diagnostics[0].listing_line, 4);
+    assert_eq!(diagnostics[0].language, Some(Language::Madlen));
+    assert_eq!(diagnostics[1].to_string(),
+               "target/listing_undefined.madlen: error: БЫЛИ OШИБKИ ПPИ BBOДE ИЛИ TPAHCЛЯЦИИ !!!");
+}
+
+#[test]
+fn test_listing_ftn_line_number() {
+    let units = vec![unit("target/listing_label.ftn", "        program test
+        goto 123
+        end
+")];
+    let listing = " Ф O P T P A H - Г Д P
+      (09.07.81)
+  001                 PROGRAM TEST
+  002                 GOTO 123
+  003                 END
+          ERRORS: 1     WARNINGS: 0
+ERROR 056 IN 00002 UNDEFINED LABEL >123<
+";
+    let diagnostics = scan_listing(listing, &units, "target/listing.lst");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].file, "target/listing_label.ftn");
+    assert_eq!(diagnostics[0].line, Some(2));
+    assert_eq!(diagnostics[0].message, "ERROR 056 IN 00002 UNDEFINED LABEL >123<");
+}
+
+#[test]
+fn test_listing_second_unit() {
+    let units = vec![
+        unit("target/listing_first.ftn", "        subroutine first
+        end
+"),
+        unit("target/listing_second.fortran", "        program test
+        x = 1
+        a = b
+        end
+"),
+    ];
+    let listing = " Ф O P T P A H - Г Д P
+  001                 SUBROUTINE FIRST
+  002                 END
+ Ф O P T P A H
+   /16.07.73/
+                  PROGRAM TEST
+                  X = 1
+                  A = B
+                  END
+******HEOПИCAHHЫЙ ИДEHTИФИKATOP           B
+";
+    let diagnostics = scan_listing(listing, &units, "target/listing.lst");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].to_string(),
+               "target/listing_second.fortran:3:13: error: HEOПИCAHHЫЙ ИДEHTИФИKATOP B");
+}
+
+#[test]
+fn test_listing_loader_message() {
+    let units = vec![unit("target/listing_main.assem", " main: ,name,
+        ,*74,
+        ,end,
+")];
+    let listing = " ABTOKOД  MADLEN
+   (1.10.72)
+                    MAIN    :  , NAME,
+ 0000                          , *74 ,
+ ЧИCЛO ПEPФ. 0003      ЧИCЛO OШИБ. OПEPATOPOB  0000
+ OTCYTCTBYET  PROGRAM
+";
+    let diagnostics = scan_listing(listing, &units, "target/listing.lst");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].to_string(), "target/listing.lst:6: error: OTCYTCTBYET PROGRAM");
+    assert_eq!(diagnostics[0].language, None);
+}
+
+#[test]
+fn test_listing_forex_warning() {
+    let units = vec![unit("target/listing_unused.forex", "        program test
+        a = b
+        end
+")];
+    let listing = "                F O R E X
+                  PROGRAM TEST
+                  A = B
+                  END
+ ЗHAЧEH. A       HE ИCПOЛЬЗYETCЯ
+ ЗHAЧEH. B       HE OПPEДEЛEHO
+";
+    let diagnostics = scan_listing(listing, &units, "target/listing.lst");
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].to_string(),
+               "target/listing_unused.forex:2:9: warning: ЗHAЧEH. A HE ИCПOЛЬЗYETCЯ");
+    assert_eq!(diagnostics[1].severity, Severity::Error);
+}
+
+#[test]
+fn test_listing_warning_options() {
+    let units = vec![unit("target/listing_warning.forex", "        program test
+        a = 1
+        end
+")];
+    let listing = "                F O R E X
+                  PROGRAM TEST
+                  A = 1
+                  END
+ ЗHAЧEH. A       HE ИCПOЛЬЗYETCЯ
+";
+    let diagnostics = scan_listing(listing, &units, "target/listing.lst");
+    assert!(!warnings::has_errors(&diagnostics));
+
+    let options = parse_and_process(vec!["besmc", "-Werror", "hello.forex"]);
+    let promoted = warnings::apply_warning_options(diagnostics.clone(), &options);
+    assert_eq!(promoted[0].severity, Severity::Error);
+    assert!(warnings::has_errors(&promoted));
+
+    let options = parse_and_process(vec!["besmc", "-w", "hello.forex"]);
+    assert!(warnings::apply_warning_options(diagnostics, &options).is_empty());
+}
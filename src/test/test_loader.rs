@@ -0,0 +1,140 @@
+use crate::test::*;
+
+#[test]
+fn test_loader_bemsh_module() {
+    let listing = "ИПM MAKPO-БEMШ BEP.06/78
+MAIN     HAM=01000   ДЛИHA MOДЛ=00001  ДЛИHA ПPOГ=00010 BXOДH=00001
+ЧИCЛO OШИБOK=0000. MAKC CEPЬEЗH=0.
+ ДЛИHA БИБЛИOTEKИ  001 01
+";
+    let summary = parse_loader_output(listing);
+
+    assert_eq!(summary.modules, vec![ModuleSize {
+        name: "MAIN".to_string(),
+        words: 8,
+        address: Some(0o1000),
+        module_words: Some(1),
+        entry: Some(1),
+    }]);
+    assert_eq!(summary.library_length.as_deref(), Some("001 01"));
+    assert_eq!(summary.library_zones, None);
+}
+
+#[test]
+fn test_loader_many_modules() {
+    let listing = " F O R E X ИПM AH CCCP 4.13 OT 11.09.85
+ KOMAHД 00013    KOHCTAHT 00003    ПAMЯTЬ ДЛЯ ПEPEMEHHЫX 00000
+ ДЛИHA ПOДПPOГPAMMЫ HELLO  00016
+A L G O L - Г Д P
+PROGRAM   ДЛИHA:   16 00020B BPEMЯ:  0.00 CEK. KAPT:   4
+ ДЛИHA LIBRARY 0001 ЗOH.
+";
+    let summary = parse_loader_output(listing);
+
+    let names: Vec<&str> = summary.modules.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["HELLO", "PROGRAM"]);
+    assert_eq!(summary.modules[0].words, 0o16);
+    assert_eq!(summary.modules[1].words, 16);
+    assert_eq!(summary.total_words(), 30);
+    assert_eq!(summary.library_zones, Some(1));
+}
+
+#[test]
+fn test_loader_size_report() {
+    let summary = parse_loader_output(" ДЛИHA ПOДПPOГPAMMЫ HELLO  00016
+PROGRAM   ДЛИHA:   16 00020B BPEMЯ:  0.00 CEK. KAPT:   4
+ ДЛИHA БИБЛИOTEKИ  002 30
+");
+    assert_eq!(format_size_report(ReportFormat::Text, &summary), "\
+Module      Octal   Decimal
+HELLO          16        14
+PROGRAM        20        16
+Total          36        30
+Library length: 002 30
+");
+
+    let json: serde_json::Value = serde_json::from_str(&format_size_report(ReportFormat::Json, &summary)).unwrap();
+    assert_eq!(json["total_words"], 30);
+    assert_eq!(json["modules"][1]["name"], "PROGRAM");
+    assert_eq!(json["modules"][1]["words"], 16);
+    assert_eq!(json["library_length"], "002 30");
+}
+
+#[test]
+fn test_loader_size_report_option() {
+    let options = parse_and_process(vec!["besmc", "--size-report", "hello.ftn"]);
+    assert_eq!(options.size_report, Some(ReportFormat::Text));
+
+    let options = parse_and_process(vec!["besmc", "--size-report=json", "hello.ftn"]);
+    assert_eq!(options.size_report, Some(ReportFormat::Json));
+
+    let options = parse_and_process(vec!["besmc", "hello.ftn"]);
+    assert_eq!(options.size_report, None);
+}
+
+//
+// Lengths of modules, from real listings in examples/README.md.
+//
+#[test]
+fn test_loader_module_lengths() {
+    assert_eq!(parse_loader_output(&listing_excerpt("hello.algol")).total_words(), 16);
+    assert_eq!(parse_loader_output(&listing_excerpt("hello.ftn")).total_words(), 9);
+    assert_eq!(parse_loader_output(&listing_excerpt("hello.forex")).total_words(), 0o16);
+    assert_eq!(parse_loader_output(&listing_excerpt("hello.bemsh")).total_words(), 0o10);
+}
+
+//
+// Length of the whole program, from real listings in examples/README.md.
+// Link of object files reports no modules, but the length is there.
+//
+#[test]
+fn test_loader_library_words() {
+    assert_eq!(parse_loader_output(&listing_excerpt("hello.bemsh")).library_words(), Some(1024 + 32));
+    assert_eq!(parse_loader_output(&listing_excerpt("hello.algol")).library_words(), Some(1024 + 0o17 * 32));
+    assert_eq!(parse_loader_output(&listing_excerpt("hello.c")).library_words(), Some(3 * 1024 + 3 * 32));
+
+    let summary = parse_loader_output(&listing_excerpt("caller.pascal"));
+    assert!(summary.modules.is_empty());
+    assert_eq!(summary.library_words(), Some(4 * 1024 + 3 * 32));
+
+    assert_eq!(parse_loader_output(" ДЛИHA LIBRARY 0001 ЗOH.\n").library_words(), None);
+    assert_eq!(parse_loader_output(" ДЛИHA БИБЛИOTEKИ  002 77\n").library_words(), None);
+
+    let err = BesmcError::UnknownSize { output: "hello.exe".to_string(), listing: "hello.lst".to_string() };
+    assert_eq!(err.exit_code(), 25);
+    assert_eq!(err.to_string(), "Cannot check --max-size of hello.exe: length of the program not found in hello.lst");
+}
+
+#[test]
+fn test_loader_max_size() {
+    let summary = parse_loader_output("MAIN     HAM=01000   ДЛИHA MOДЛ=00001  ДЛИHA ПPOГ=00010 BXOДH=00001
+ ДЛИHA ПOДПPOГPAMMЫ HELLO  00016
+ ДЛИHA ПOДПPOГPAMMЫ TINY  00002
+ ДЛИHA БИБЛИOTEKИ  001 01
+");
+    let largest = summary.largest_modules(2);
+    let names: Vec<&str> = largest.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["HELLO", "MAIN"]);
+
+    let err = BesmcError::TooLarge { output: "hello.exe".to_string(), words: 1056, limit: 1024, largest };
+    assert_eq!(err.exit_code(), 15);
+    assert_eq!(err.to_string(), "\
+hello.exe is too large: 1056 words (2040 octal), limit is 1024 words (2000 octal)
+Largest modules:
+  HELLO        14 words (16 octal)
+  MAIN          8 words (10 octal)");
+}
+
+#[test]
+fn test_loader_max_size_option() {
+    let options = parse_and_process(vec!["besmc", "--max-size", "32768", "hello.ftn"]);
+    assert_eq!(options.max_size, Some(32768));
+
+    let options = parse_and_process(vec!["besmc", "--max-size", "0o100000", "hello.ftn"]);
+    assert_eq!(options.max_size, Some(32768));
+
+    let options = parse_and_process(vec!["besmc", "--max-size=100000B", "hello.ftn"]);
+    assert_eq!(options.max_size, Some(32768));
+
+    assert!(CompilerOptions::try_parse_from(vec!["besmc", "--max-size", "lots", "hello.ftn"]).is_err());
+}
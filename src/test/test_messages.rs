@@ -0,0 +1,64 @@
+use crate::test::*;
+
+#[test]
+fn test_messages_normalize() {
+    assert_eq!(normalize(" ****** HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR"),
+                         " ****** НЕОПИСАННЫЙ ИДЕНТИФИКАТОР: ..... FOOBAR");
+    assert_eq!(normalize(" OШИБKИ HA CTP.    1."),
+                         " ОШИБКИ НА СТР.    1.");
+    assert_eq!(normalize("  ДЛИHA БИБЛИOTEKИ  001 01"),
+                         "  ДЛИНА БИБЛИОТЕКИ  001 01");
+    assert_eq!(normalize(" OTCYTCTBYET  PROGRAM"),
+                         " ОТСУТСТВУЕТ  PROGRAM");
+    assert_eq!(normalize(" Ф O P T P A H - Г Д P"),
+                         " Ф О Р Т Р А Н - Г Д Р");
+}
+
+#[test]
+fn test_messages_normalize_keeps_english() {
+    let lines = [
+        "ERROR 056 IN 00002 UNDEFINED LABEL >123<",
+        " PROGRAM           MADLEN-3.5",
+        "                F O R E X",
+        " 0000                          , UJ  ,FOOBAR",
+        "*library:22",
+    ];
+    for line in lines {
+        assert_eq!(normalize(line), line);
+    }
+}
+
+#[test]
+fn test_messages_translate() {
+    assert_eq!(render(" ****** HEOПИCAHHЫЙ ИДEHTИФИKATOP: ..... FOOBAR", Messages::En),
+                      " ****** UNDECLARED IDENTIFIER: ..... FOOBAR");
+    assert_eq!(render(" БЫЛИ OШИБKИ ПPИ BBOДE ИЛИ TPAHCЛЯЦИИ !!!", Messages::En),
+                      " THERE WERE ERRORS IN INPUT OR TRANSLATION !!!");
+    assert_eq!(render(" OTCYTCTBYET  PROGRAM", Messages::En),
+                      " MISSING  PROGRAM");
+    assert_eq!(render("  ДЛИHA БИБЛИOTEKИ  001 01", Messages::En),
+                      "  LIBRARY LENGTH  001 01");
+    assert_eq!(render("  ДЛИHA БИБЛИOTEKИ  001 01", Messages::Original),
+                      "  ДЛИHA БИБЛИOTEKИ  001 01");
+}
+
+#[test]
+fn test_messages_option() {
+    let options = parse_and_process(vec!["besmc", "--messages=en", "--translate-listing", "hello.ftn"]);
+    assert_eq!(options.messages, Messages::En);
+    assert!(options.translate_listing);
+
+    let options = parse_and_process(vec!["besmc", "hello.ftn"]);
+    assert_eq!(options.messages, Messages::Ru);
+    assert!(!options.translate_listing);
+}
+
+#[test]
+fn test_messages_names_stay_latin() {
+    assert_eq!(normalize(" ЗHAЧEH. A       HE ИCПOЛЬЗYETCЯ"),
+                         " ЗНАЧЕН. A       НЕ ИСПОЛЬЗУЕТСЯ");
+    assert_eq!(render(" ЗHAЧEH. B       HE OПPEДEЛEHO", Messages::En),
+                      " VALUE B IS UNDEFINED");
+    assert_eq!(render(" ЗHAЧEH. 123  :  HE OПPEДEЛEHO", Messages::En),
+                      " VALUE 123  : IS UNDEFINED");
+}
@@ -1,4 +1,3 @@
-use crate::*;
 use crate::test::*;
 
 fn compile_exe_negative(contents: &str, source_file: &str) {
@@ -12,10 +11,8 @@ fn compile_exe_negative(contents: &str, source_file: &str) {
         ..Default::default()
     };
 
-    // Try to compile and make sure it panicked.
-    let result = panic::catch_unwind(|| {
-        compile_files(&options);
-    });
+    // Try to compile and make sure it failed.
+    let result = Build::new(options).run();
     assert!(result.is_err(), "Compilation did not fail");
 }
 
@@ -11,7 +11,7 @@ fn compile_obj(source_file_name: &str, output_file_name: &str) {
         stop_at_object: true,
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with(&listing_file, " ДЛИHA LIBRARY"), " ДЛИHA LIBRARY 0001 ЗOH.");
 }
@@ -60,3 +60,17 @@ fn test_pascal_obj() {
 fn test_c_obj() {
     compile_obj("examples/hello.c", "target/lib_c.obj");
 }
+
+//
+// Standard array is taken from the listing before it is rewritten by --translate-listing.
+//
+#[test]
+fn test_emit_std_translate_listing() {
+    let options = parse_and_process(vec!["besmc", "-c", "--emit=std", "--translate-listing", "--messages=en",
+                                         "-o", "target/emit_translated.std", "examples/hello.ftn"]);
+    compile_files(&options).unwrap();
+
+    let array = StdArray::load("target/emit_translated.std").unwrap();
+    assert!(!array.modules.is_empty());
+    assert_eq!(find_line_starting_with("target/emit_translated.lst", " ДЛИHA LIBRARY"), "");
+}
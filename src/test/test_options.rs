@@ -24,3 +24,69 @@ fn test_mixed_file_types() {
         "code.assem".to_string(),
         "obj.obj".to_string()]);
 }
+
+#[test]
+fn test_verbose_and_dry_run() {
+    let args = vec!["besmc", "-v", "--dry-run", "hello.ftn"];
+    let options = parse_and_process(args);
+
+    assert!(options.verbose);
+    assert!(options.dry_run);
+}
+
+#[test]
+fn test_parallel_jobs() {
+    let args = vec!["besmc", "-c", "-j", "4", "a.ftn", "b.ftn"];
+    let options = parse_and_process(args);
+
+    assert_eq!(options.jobs, Some(4));
+    assert!(options.stop_at_object);
+}
+
+#[test]
+fn test_build_without_files() {
+    let result = Build::new(CompilerOptions::default()).run();
+
+    assert!(matches!(result, Err(BesmcError::NoInput)));
+}
+
+#[test]
+fn test_build_unknown_extension() {
+    let options = CompilerOptions {
+        output_file: Some("target/unknown_extension.exe".to_string()),
+        files: vec!["examples/README.md".to_string()],
+        ..Default::default()
+    };
+    let err = Build::new(options).run().unwrap_err();
+
+    assert!(matches!(&err, BesmcError::UnknownExtension { file } if file == "examples/README.md"));
+    assert_eq!(err.exit_code(), 8);
+}
+
+#[test]
+fn test_work_directories_are_private() {
+    let first = workdir::WorkDir::create(false).unwrap();
+    let second = workdir::WorkDir::create(false).unwrap();
+    let first_path = first.path().to_path_buf();
+
+    assert_ne!(first.path(), second.path());
+    assert!(first_path.is_dir());
+    let mode = std::os::unix::fs::PermissionsExt::mode(&first_path.metadata().unwrap().permissions());
+    assert_eq!(mode & 0o777, 0o700);
+    drop(first);
+    assert!(!first_path.exists());
+}
+
+#[test]
+fn test_split_objects_output_for_many_files() {
+    let options = CompilerOptions {
+        output_file: Some("target/split.obj".to_string()),
+        stop_at_object: true,
+        split_objects: true,
+        files: vec!["examples/hello.ftn".to_string(), "examples/callee.ftn".to_string()],
+        ..Default::default()
+    };
+    let err = Build::new(options).run().unwrap_err();
+
+    assert!(matches!(&err, BesmcError::OutputForManyFiles { output } if output == "target/split.obj"));
+}
@@ -10,7 +10,7 @@ fn test_pascal_to_fortran() {
         stop_at_object: true,
         files: vec!["examples/caller.pascal".to_string()],
         ..Default::default()
-    });
+    }).unwrap();
 
     // Compile subroutine written in Fortran.
     compile_files(&CompilerOptions {
@@ -18,7 +18,7 @@ fn test_pascal_to_fortran() {
         stop_at_object: true,
         files: vec!["examples/callee.ftn".to_string()],
         ..Default::default()
-    });
+    }).unwrap();
 
     // Link both together.
     compile_files(&CompilerOptions {
@@ -28,7 +28,7 @@ fn test_pascal_to_fortran() {
             "target/callee.obj".to_string(),
         ],
         ..Default::default()
-    });
+    }).unwrap();
 
     assert_eq!(find_line_starting_with("target/pascal_to_fortran.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  004 03");
 }
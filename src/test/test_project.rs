@@ -0,0 +1,96 @@
+use crate::test::*;
+
+const MANIFEST_TEXT: &str = r#"
+[[target]]
+name = "app"
+kind = "exe"
+sources = ["main.pascal"]
+deps = ["util"]
+entry = "main"
+libraries = ["m"]
+
+[[target]]
+name = "util"
+kind = "lib"
+sources = ["a.ftn", "b.bemsh"]
+deps = ["base"]
+
+[[target]]
+name = "base"
+kind = "lib"
+sources = ["base.ftn"]
+output = "lib/libbase"
+"#;
+
+//
+// Dependencies are built first, each one once.
+//
+#[test]
+fn test_project_build_order() {
+    let project = Project::parse(MANIFEST_TEXT, "besmc.toml").unwrap();
+    let names = |targets: Vec<&Target>| targets.iter().map(|t| t.name.clone()).collect::<Vec<_>>();
+
+    assert_eq!(names(project.build_order(None, "besmc.toml").unwrap()), vec!["base", "util", "app"]);
+    assert_eq!(names(project.build_order(Some("util"), "besmc.toml").unwrap()), vec!["base", "util"]);
+    assert_eq!(project.build_order(Some("other"), "besmc.toml").unwrap_err().exit_code(), 22);
+}
+
+//
+// Executable is linked with all libraries it depends on.
+//
+#[test]
+fn test_project_target_options() {
+    let project = Project::parse(MANIFEST_TEXT, "besmc.toml").unwrap();
+    let options = parse_and_process(vec!["besmc", "-v", "-l", "c", "build"]);
+
+    let app = project.target_options(project.target("app").unwrap(), &options, "besmc.toml").unwrap();
+    assert_eq!(app.files, vec!["main.pascal", "lib/libbase.obj", "util.obj"]);
+    assert_eq!(app.output_file.as_deref(), Some("app.exe"));
+    assert!(!app.stop_at_object);
+    assert_eq!(app.entry.as_deref(), Some("main"));
+    assert_eq!(app.libraries, vec!["c", "m"]);
+    assert!(app.verbose);
+    assert_eq!(app.command, None);
+
+    let base = project.target_options(project.target("base").unwrap(), &options, "besmc.toml").unwrap();
+    assert_eq!(base.files, vec!["base.ftn"]);
+    assert_eq!(base.output_file.as_deref(), Some("lib/libbase.obj"));
+    assert!(base.stop_at_object);
+}
+
+#[test]
+fn test_project_bad_manifest() {
+    let bad = |text: &str| Project::parse(text, "besmc.toml").unwrap_err().to_string();
+
+    assert_eq!(bad(""), "Bad project manifest besmc.toml: no targets");
+    assert_eq!(bad("[[target]]\nname = \"a\"\nkind = \"exe\"\nsources = []\n"),
+               "Bad project manifest besmc.toml: target a: no sources");
+    assert_eq!(bad("[[target]]\nname = \"a\"\nkind = \"exe\"\nsources = [\"a.ftn\"]\ndeps = [\"b\"]\n"),
+               "Bad project manifest besmc.toml: target a: unknown dependency b");
+    assert_eq!(bad("[[target]]\nname = \"a\"\nkind = \"lib\"\nsources = [\"a.ftn\"]\n\n[[target]]\nname = \"b\"\nkind = \"lib\"\nsources = [\"b.ftn\"]\ndeps = [\"c\"]\n[[target]]\nname = \"c\"\nkind = \"exe\"\nsources = [\"c.ftn\"]\n"),
+               "Bad project manifest besmc.toml: target b: dependency c is not a library");
+    assert!(bad("[[target]]\nname = \"a\"\nkind = \"dll\"\nsources = [\"a.ftn\"]\n").contains("unknown variant"));
+}
+
+#[test]
+fn test_project_cycle() {
+    let text = "[[target]]\nname = \"a\"\nkind = \"lib\"\nsources = [\"a.ftn\"]\ndeps = [\"b\"]\n\
+                [[target]]\nname = \"b\"\nkind = \"lib\"\nsources = [\"b.ftn\"]\ndeps = [\"a\"]\n";
+    let project = Project::parse(text, "besmc.toml").unwrap();
+    let err = project.build_order(None, "besmc.toml").unwrap_err();
+
+    assert_eq!(err.exit_code(), 22);
+    assert_eq!(err.to_string(), "Bad project manifest besmc.toml: dependency cycle: a -> b -> a");
+}
+
+//
+// Files of the manifest are relative to its directory.
+//
+#[test]
+fn test_project_dry_run() {
+    let options = parse_and_process(vec!["besmc", "--dry-run", "build", "--manifest", "examples/besmc.toml", "caller"]);
+    let artifacts = Build::new(options).run().unwrap();
+
+    assert_eq!(artifacts.listing, "examples/caller.lst");
+    assert!(!std::path::Path::new("examples/caller.exe").exists());
+}
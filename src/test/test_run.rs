@@ -0,0 +1,60 @@
+use crate::test::*;
+
+#[test]
+fn test_run_options() {
+    let options = parse_and_process(vec!["besmc", "-o", "target/run", "run", "-k", "-i", "data.txt", "hello.ftn"]);
+
+    assert_eq!(options.output_file.as_deref(), Some("target/run"));
+    assert_eq!(options.command, Some(Command::Run {
+        input: Some("data.txt".to_string()),
+        keep: true,
+        files: vec!["hello.ftn".to_string()],
+    }));
+}
+
+//
+// Kept executable is named as by a usual build.
+//
+#[test]
+fn test_run_keep_dry_run() {
+    let options = parse_and_process(vec!["besmc", "--dry-run", "-o", "target/run_keep", "run", "-k", "examples/hello.ftn"]);
+    let artifacts = Build::new(options).run().unwrap();
+
+    assert_eq!(artifacts.listing, "target/run_keep.lst");
+    assert_eq!(artifacts.status, None);
+}
+
+//
+// Dry run shows the program in the work directory as $WORKDIR, and a kept one by absolute name.
+//
+#[test]
+fn test_run_dry_run_command() {
+    let workdir = workdir::WorkDir::placeholder();
+    let command = run::program_command(&workdir.file("hello.exe"), "examples/hello.ftn", &workdir);
+
+    assert_eq!(command.command_line(std::path::Path::new("/home/user"), Some("data.txt"), None),
+               "cd /home/user && $WORKDIR/hello.exe < data.txt");
+
+    let command = run::program_command("target/run_keep.exe", "examples/hello.ftn", &workdir);
+    let program = std::env::current_dir().unwrap().join("target/run_keep.exe");
+
+    assert_eq!(command.to_string(), plan::shell_quote(&program.to_string_lossy()));
+}
+
+#[test]
+fn test_run_needs_files() {
+    let result = CompilerOptions::try_parse_from(vec!["besmc", "run", "-k"]);
+    assert!(result.is_err());
+}
+
+//
+// Program runs through dubna, and its status is returned.
+//
+#[test]
+fn test_run_ftn() {
+    let options = parse_and_process(vec!["besmc", "run", "examples/hello.ftn"]);
+    let artifacts = Build::new(options).run().unwrap();
+
+    assert_eq!(artifacts.status, Some(0));
+    assert_eq!(artifacts.executable, None);
+}
@@ -15,7 +15,7 @@ fn test_stdarray_exe() {
         output_file: Some("target/hello_std.exe".to_string()),
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/hello_std.lst", " ДЛИHA БИБЛИOTEKИ"), " ДЛИHA БИБЛИOTEKИ  002 17");
 }
@@ -31,7 +31,86 @@ fn test_stdarray_obj() {
         stop_at_object: true,
         ..Default::default()
     };
-    compile_files(&options);
+    compile_files(&options).unwrap();
 
     assert_eq!(find_line_starting_with("target/lib_stdarray.lst", " ДЛИHA LIBRARY"), " ДЛИHA LIBRARY 0001 ЗOH.");
 }
+
+//
+// Standard array is parsed into records, and written back the same.
+//
+#[test]
+fn test_stdarray_parse() {
+    let text = std::fs::read_to_string("examples/stdarray.std").unwrap();
+    let array = StdArray::parse(&text).unwrap();
+
+    assert_eq!(array.modules.len(), 1);
+    let module = &array.modules[0];
+    assert_eq!(module.name, "PROGRAM");
+    assert_eq!(module.records.len(), 4);
+    assert_eq!(module.records[3].code, 0o77761);
+    assert_eq!(module.records[3].number, 4);
+    assert_eq!(module.words().count(), 64);
+    assert_eq!(module.words().next(), Some(0o5541515600000000));
+    assert_eq!(array.to_string(), text);
+}
+
+#[test]
+fn test_stdarray_bad_word() {
+    let text = "`77761 PROGRAM  1\n`5541515600000000\n`554151560000000\n";
+    assert_eq!(StdArray::parse(text), Err((3, "bad word `554151560000000: must be 16 octal digits".to_string())));
+
+    let text = "`77761 PROGRAM  1\n`5541515600000008\n";
+    assert_eq!(StdArray::parse(text).unwrap_err().0, 2);
+}
+
+#[test]
+fn test_stdarray_bad_structure() {
+    let text = "`5541515600000000\n";
+    assert_eq!(StdArray::parse(text), Err((1, "word before the first record header".to_string())));
+
+    let text = "`77761 PROGRAM  1\n`5541515600000000\n`77761 PROGRAM  3\n";
+    assert_eq!(StdArray::parse(text),
+               Err((3, "record 3 of module PROGRAM is out of order, expected record 2".to_string())));
+
+    let text = "`77761 PROGRAM  1\n`77761 OTHER    2\n";
+    assert_eq!(StdArray::parse(text), Err((2, "record 2 of module PROGRAM is named OTHER".to_string())));
+
+    assert!(StdArray::parse("").is_err());
+}
+
+//
+// Malformed standard array fails the build before Dubna is run.
+//
+#[test]
+fn test_stdarray_malformed_input() {
+    std::fs::write("target/malformed.std", "`77761 PROGRAM  1\nPROGRAM\n").unwrap();
+    let options = CompilerOptions {
+        files: vec!["target/malformed.std".to_string()],
+        ..Default::default()
+    };
+    let err = compile_files(&options).unwrap_err();
+
+    assert_eq!(err.exit_code(), 18);
+    assert_eq!(err.to_string(), "target/malformed.std:2: bad standard array: bad line \"PROGRAM\": must start with a backquote");
+}
+
+//
+// Punched cards are collected from the listing, between other lines.
+//
+#[test]
+fn test_stdarray_from_listing() {
+    let listing = " Ф O P T P A H - Г Д P
+`77761 PROGRAM  1
+`5541515600000000
+ ЧИCЛO OШИБOK 0
+`77761 PROGRAM  2
+`0000000000000001
+";
+    let array = StdArray::from_listing(listing).unwrap();
+    assert_eq!(array.to_string(), "`77761 PROGRAM  1\n`5541515600000000\n`77761 PROGRAM  2\n`0000000000000001\n");
+
+    let listing = " LISTING\n`77761 PROGRAM  1\n`55415156\n";
+    assert_eq!(StdArray::from_listing(listing).unwrap_err().0, 3);
+    assert!(StdArray::from_listing(" LISTING\n").is_err());
+}
@@ -0,0 +1,38 @@
+use clap::ValueEnum;
+
+use super::CompilerOptions;
+use super::listing::{Diagnostic, Severity};
+
+//
+// Values of -W option.
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WarningOption {
+    /// Treat warnings as errors
+    Error,
+}
+
+//
+// Apply -w and -Werror to messages found in the listing.
+// With -w, warnings and notes are dropped.
+// With -Werror, warnings become errors.
+//
+pub fn apply_warning_options(diagnostics: Vec<Diagnostic>, options: &CompilerOptions) -> Vec<Diagnostic> {
+    let werror = options.warning_options.contains(&WarningOption::Error);
+    diagnostics.into_iter()
+               .filter(|diagnostic| !options.no_warnings || diagnostic.severity == Severity::Error)
+               .map(|mut diagnostic| {
+                   if werror && diagnostic.severity == Severity::Warning {
+                       diagnostic.severity = Severity::Error;
+                   }
+                   diagnostic
+               })
+               .collect()
+}
+
+//
+// Whether any message fails the build.
+//
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::BesmcError;
+
+// Distinguishes directories created by the same process.
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+//
+// Private directory for temporary files of one build:
+// Dubna script, output.bin, persNN.bin, libc.bin and intermediate
+// files of the Pascal and C compilers.
+// Dubna runs with this directory as current, so parallel builds
+// do not clobber each other. The directory is removed when dropped,
+// unless asked to keep it.
+//
+#[derive(Debug)]
+pub struct WorkDir {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl WorkDir {
+    //
+    // Create a new unique directory under the system temporary directory.
+    // Only the user can access it, as sources and listings are put there.
+    //
+    pub fn create(keep: bool) -> Result<WorkDir, BesmcError> {
+        let base = std::env::temp_dir();
+        loop {
+            let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path = base.join(format!("besmc-{}-{}", std::process::id(), count));
+            match fs::DirBuilder::new().mode(0o700).create(&path) {
+                Ok(()) => return Ok(WorkDir { path, keep }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue, // Left by another process
+                Err(e) => return Err(BesmcError::io("create directory", &path.to_string_lossy(), e)),
+            }
+        }
+    }
+
+    //
+    // Work directory which is not created, for showing plans.
+    // Its name is "$WORKDIR", so plans can be run by shell.
+    //
+    pub fn placeholder() -> WorkDir {
+        WorkDir { path: PathBuf::from("$WORKDIR"), keep: true }
+    }
+
+    pub fn is_placeholder(&self) -> bool {
+        self.path == Path::new("$WORKDIR")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    //
+    // Full name of a file in this directory.
+    //
+    pub fn file(&self, name: &str) -> String {
+        self.path.join(name).to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}